@@ -4,5 +4,8 @@ import type { paths } from "./schema.d.ts";
 
 const fetchClient = createFetchClient<paths>({
   baseUrl: "http://localhost:5173",
+  headers: {
+    Authorization: `Bearer ${import.meta.env.VITE_CATFLEET_API_KEY}`,
+  },
 });
 export const $api = createClient(fetchClient);
@@ -8,7 +8,17 @@ export default defineConfig({
   plugins: [react()],
   server: {
     proxy: {
-      '/status': { target: backendUrl }
+      '/status': { target: backendUrl },
+      '/views': { target: backendUrl },
+      '/systems': { target: backendUrl },
+      '/markets': { target: backendUrl },
+      '/ledger': { target: backendUrl },
+      '/surveys': { target: backendUrl },
+      '/events': { target: backendUrl },
+      '/snapshot': { target: backendUrl },
+      '/contracts': { target: backendUrl },
+      '/history': { target: backendUrl },
+      '/fleet': { target: backendUrl }
     }
   }
 })
@@ -0,0 +1,333 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::time::Instant;
+use tracing::{event, Level};
+
+use crate::{
+    client::Observer,
+    model::{ApiResponseData, Contract, Market, Ship, Waypoint},
+    store::{Record, Store},
+};
+
+/// A value together with the time it was fetched from the API.
+#[derive(Debug, Clone)]
+struct Cached<T> {
+    value: T,
+    fetched: Instant,
+}
+
+impl<T: Clone> Cached<T> {
+    fn new(value: T) -> Self {
+        Self {
+            value,
+            fetched: Instant::now(),
+        }
+    }
+
+    fn fresh(&self, max_age: Duration) -> Option<T> {
+        (self.fetched.elapsed() <= max_age).then(|| self.value.clone())
+    }
+}
+
+/// Recently seen API objects, kept up to date by observing the responses of the client.
+///
+/// Partial updates, like a new `ShipNav` after navigating, are applied to the
+/// cached objects, but do not count as fetching the whole object again.
+///
+/// Objects that are not in memory are looked up in the [`Store`],
+/// so that they survive restarts.
+#[derive(Debug)]
+pub struct Cache {
+    inner: Mutex<Inner>,
+    store: Arc<Store>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    ships: HashMap<String, Cached<Ship>>,
+    /// When the full list of our ships was last stored.
+    ships_listed: Option<Instant>,
+    waypoints: HashMap<String, Cached<Waypoint>>,
+    markets: HashMap<String, Cached<Market>>,
+    /// All of our contracts, as of the last time they were listed.
+    contracts: Option<Cached<Vec<Contract>>>,
+    /// Whether our agent or the list of our ships was seen since the cache was created or cleared.
+    loaded: bool,
+}
+
+impl Cache {
+    pub fn new(store: Arc<Store>) -> Self {
+        Self {
+            inner: Mutex::default(),
+            store,
+        }
+    }
+
+    /// The ship with the given symbol, if it was fetched within `max_age`.
+    pub fn ship(&self, symbol: &str, max_age: Duration) -> Option<Ship> {
+        let cached = self
+            .inner
+            .lock()
+            .unwrap()
+            .ships
+            .get(symbol)
+            .and_then(|ship| ship.fresh(max_age));
+        cached.or_else(|| self.stored(symbol, max_age))
+    }
+
+    /// The waypoint with the given symbol, if it was fetched within `max_age`.
+    pub fn waypoint(&self, symbol: &str, max_age: Duration) -> Option<Waypoint> {
+        let cached = self
+            .inner
+            .lock()
+            .unwrap()
+            .waypoints
+            .get(symbol)
+            .and_then(|waypoint| waypoint.fresh(max_age));
+        cached.or_else(|| self.stored(symbol, max_age))
+    }
+
+    /// The market at the given waypoint, if it was fetched within `max_age`.
+    pub fn market(&self, symbol: &str, max_age: Duration) -> Option<Market> {
+        let cached = self
+            .inner
+            .lock()
+            .unwrap()
+            .markets
+            .get(symbol)
+            .and_then(|market| market.fresh(max_age));
+        cached.or_else(|| self.stored(symbol, max_age))
+    }
+
+    /// All of our ships, if the full list was fetched within `max_age`.
+    pub fn ships(&self, max_age: Duration) -> Option<Vec<Ship>> {
+        let inner = self.inner.lock().unwrap();
+        if inner.ships_listed?.elapsed() > max_age {
+            return None;
+        }
+        let mut ships: Vec<_> = inner
+            .ships
+            .values()
+            .map(|ship| ship.value.clone())
+            .collect();
+        ships.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        Some(ships)
+    }
+
+    fn stored<T: Record>(&self, symbol: &str, max_age: Duration) -> Option<T> {
+        match self.store.get(symbol) {
+            Ok(stored) => stored?.fresh(max_age),
+            Err(err) => {
+                event!(Level::WARN, "Failed to read `{symbol}` from store: {err:?}");
+                None
+            }
+        }
+    }
+
+    /// All of our contracts, if they were listed within `max_age`.
+    pub fn contracts(&self, max_age: Duration) -> Option<Vec<Contract>> {
+        self.inner
+            .lock()
+            .unwrap()
+            .contracts
+            .as_ref()?
+            .fresh(max_age)
+    }
+
+    /// Whether our agent or our ships were fetched, so that there is state to work with.
+    pub fn loaded(&self) -> bool {
+        self.inner.lock().unwrap().loaded
+    }
+
+    /// Forget everything, for example because the universe was reset.
+    pub fn clear(&self) {
+        *self.inner.lock().unwrap() = Inner::default();
+    }
+
+    /// Store a full list of our contracts.
+    /// The API returns them page by page, so the client can't do this on its own.
+    pub fn set_contracts(&self, contracts: Vec<Contract>) {
+        self.inner.lock().unwrap().contracts = Some(Cached::new(contracts));
+    }
+
+    /// Store a full list of our ships, replacing all ships we knew of before.
+    pub fn set_ships(&self, ships: Vec<Ship>) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.ships = ships
+            .into_iter()
+            .map(|ship| (ship.symbol.clone(), Cached::new(ship)))
+            .collect();
+        inner.ships_listed = Some(Instant::now());
+        inner.loaded = true;
+    }
+}
+
+impl Observer for Cache {
+    fn observe(&self, ship: Option<&str>, data: &ApiResponseData) {
+        let mut inner = self.inner.lock().unwrap();
+
+        match data {
+            ApiResponseData::GetShip(s)
+            | ApiResponseData::ShipPurchase { ship: s, .. }
+            | ApiResponseData::RepairShip { ship: s, .. } => {
+                inner
+                    .ships
+                    .insert(s.symbol.clone(), Cached::new(s.as_ref().clone()));
+            }
+            ApiResponseData::ListShips(ships) => {
+                for s in ships {
+                    inner.ships.insert(s.symbol.clone(), Cached::new(s.clone()));
+                }
+                inner.loaded = true;
+            }
+            ApiResponseData::GetAgent(_) | ApiResponseData::RegisterAgent(_) => {
+                inner.loaded = true;
+            }
+            ApiResponseData::GetWaypoint(waypoint)
+            | ApiResponseData::CreateChart { waypoint, .. } => {
+                inner
+                    .waypoints
+                    .insert(waypoint.symbol.clone(), Cached::new(waypoint.clone()));
+            }
+            ApiResponseData::ListWaypoints(waypoints) => {
+                for waypoint in waypoints {
+                    inner
+                        .waypoints
+                        .insert(waypoint.symbol.clone(), Cached::new(waypoint.clone()));
+                }
+            }
+            ApiResponseData::GetMarket(market) => {
+                inner
+                    .markets
+                    .insert(market.symbol.clone(), Cached::new(market.clone()));
+            }
+            ApiResponseData::GetContract(contract)
+            | ApiResponseData::NegotiateContract { contract }
+            | ApiResponseData::UpdateContract { contract, .. } => {
+                if let Some(contracts) = &mut inner.contracts {
+                    contracts.value.retain(|c| c.id != contract.id);
+                    contracts.value.push(contract.clone());
+                }
+            }
+            _ => {}
+        }
+
+        // Apply partial updates to the ship the request was about.
+        let Some(symbol) = ship else {
+            return;
+        };
+        if let ApiResponseData::ScrapShip { .. } = data {
+            inner.ships.remove(symbol);
+            return;
+        }
+        if let Some(Cached { value: ship, .. }) = inner.ships.get_mut(symbol) {
+            ship.apply(data);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contract(id: &str, accepted: bool) -> Contract {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "factionSymbol": "COSMIC",
+            "type": "PROCUREMENT",
+            "terms": {
+                "deadline": "2025-01-01T00:00:00.000Z",
+                "payment": { "onAccepted": 1000, "onFulfilled": 5000 },
+                "deliver": []
+            },
+            "accepted": accepted,
+            "fulfilled": false,
+            "expiration": "2025-01-01T00:00:00.000Z",
+            "deadlineToAccept": "2025-01-01T00:00:00.000Z"
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn contracts_expire_and_are_updated_in_place() {
+        tokio::time::pause();
+        let cache = Cache::new(Arc::new(Store::open_in_memory().unwrap()));
+        cache.set_contracts(vec![contract("a", false), contract("b", false)]);
+
+        cache.observe(
+            None,
+            &ApiResponseData::UpdateContract {
+                agent: None,
+                contract: contract("a", true),
+                cargo: None,
+            },
+        );
+
+        let contracts = cache.contracts(Duration::from_secs(60)).unwrap();
+        assert_eq!(contracts.len(), 2);
+        assert!(contracts.iter().any(|c| c.id == "a" && c.accepted));
+
+        tokio::time::advance(Duration::from_secs(61)).await;
+        assert!(cache.contracts(Duration::from_secs(60)).is_none());
+    }
+
+    #[test]
+    fn jettisoning_updates_the_cargo() {
+        let cache = Cache::new(Arc::new(Store::open_in_memory().unwrap()));
+        cache.set_ships(vec![crate::model::fixtures::ship("CAT-1", "X1-A-1")]);
+        let data: ApiResponseData = serde_json::from_value(serde_json::json!({
+            "cargo": { "capacity": 15, "units": 3, "inventory": [
+                { "symbol": "IRON_ORE", "name": "", "description": "", "units": 3 }
+            ] }
+        }))
+        .unwrap();
+
+        cache.observe(Some("CAT-1"), &data);
+
+        let ship = cache.ship("CAT-1", Duration::from_secs(60)).unwrap();
+        assert_eq!(ship.cargo.units, 3);
+    }
+
+    #[tokio::test]
+    async fn lists_ships_only_while_the_list_is_fresh() {
+        tokio::time::pause();
+        let cache = Cache::new(Arc::new(Store::open_in_memory().unwrap()));
+        assert!(cache.ships(Duration::from_secs(30)).is_none());
+
+        cache.set_ships(vec![
+            crate::model::fixtures::ship("CAT-2", "X1-A-1"),
+            crate::model::fixtures::ship("CAT-1", "X1-A-1"),
+        ]);
+        let ships = cache.ships(Duration::from_secs(30)).unwrap();
+        assert_eq!(ships[0].symbol, "CAT-1");
+        assert_eq!(ships.len(), 2);
+
+        tokio::time::advance(Duration::from_secs(31)).await;
+        assert!(cache.ships(Duration::from_secs(30)).is_none());
+    }
+
+    #[test]
+    fn is_loaded_once_the_agent_was_seen() {
+        let cache = Cache::new(Arc::new(Store::open_in_memory().unwrap()));
+        assert!(!cache.loaded());
+        let data: ApiResponseData = serde_json::from_value(serde_json::json!({
+            "accountId": "account",
+            "symbol": "CAT",
+            "headquarters": "X1-A-1",
+            "credits": 100000,
+            "startingFaction": "COSMIC",
+            "shipCount": 2
+        }))
+        .unwrap();
+
+        cache.observe(None, &data);
+        assert!(cache.loaded());
+
+        cache.clear();
+        assert!(!cache.loaded());
+    }
+}
@@ -1,6 +1,10 @@
 use std::{
     future::Future,
     pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
     task::{ready, Poll},
     time::Duration,
 };
@@ -12,6 +16,7 @@ use tracing::{event, Level};
 pub struct RateLimitWithBurstLayer {
     rate_default: Rate,
     rate_burst: Rate,
+    remaining: Option<Arc<AtomicU64>>,
 }
 
 impl RateLimitWithBurstLayer {
@@ -27,15 +32,28 @@ impl RateLimitWithBurstLayer {
         Self {
             rate_default,
             rate_burst,
+            remaining: None,
         }
     }
+
+    /// Publish the number of remaining requests to `remaining`
+    /// whenever it changes.
+    pub fn with_remaining_gauge(mut self, remaining: Arc<AtomicU64>) -> Self {
+        self.remaining = Some(remaining);
+        self
+    }
 }
 
 impl<S> tower_layer::Layer<S> for RateLimitWithBurstLayer {
     type Service = RateLimitWithBurst<S>;
 
     fn layer(&self, service: S) -> Self::Service {
-        RateLimitWithBurst::new(service, self.rate_default, self.rate_burst)
+        let mut service = RateLimitWithBurst::new(service, self.rate_default, self.rate_burst);
+        if let Some(remaining) = &self.remaining {
+            remaining.store(service.rem, Ordering::Relaxed);
+            service.remaining = remaining.clone();
+        }
+        service
     }
 }
 
@@ -51,6 +69,7 @@ pub struct RateLimitWithBurst<T> {
     until_default: Instant,
     until_burst: Instant,
     rem: u64,
+    remaining: Arc<AtomicU64>,
     sleep: Pin<Box<Sleep>>,
 }
 
@@ -65,6 +84,8 @@ impl<T> RateLimitWithBurst<T> {
     pub fn new(inner: T, rate_default: Rate, rate_burst: Rate) -> Self {
         let until = Instant::now();
         let state = State::Ready;
+        // The total amount of available requests is the default bucket + burst bucket.
+        let rem = rate_default.num() + rate_burst.num();
 
         Self {
             inner,
@@ -73,8 +94,8 @@ impl<T> RateLimitWithBurst<T> {
             state,
             until_default: until,
             until_burst: until,
-            // The total amount of available requests is the default bucket + burst bucket.
-            rem: rate_default.num() + rate_burst.num(),
+            rem,
+            remaining: Arc::new(AtomicU64::new(rem)),
             sleep: Box::pin(tokio::time::sleep_until(until)),
         }
     }
@@ -158,6 +179,8 @@ where
             event!(Level::TRACE, rem = self.rem, "refilled burst bucket");
         }
 
+        self.remaining.store(self.rem, Ordering::Relaxed);
+
         // Go back to Ready state.
         self.state = State::Ready;
 
@@ -214,6 +237,8 @@ where
                     self.state = State::Limited;
                 }
 
+                self.remaining.store(self.rem, Ordering::Relaxed);
+
                 self.inner.call(req)
             }
             State::Limited => panic!("service not ready; poll_ready must be called first"),
@@ -328,4 +353,30 @@ mod tests {
         assert_pending!(service.poll_ready());
         assert_pending!(handle.poll_request());
     }
+
+    #[tokio::test]
+    async fn publishes_remaining_requests() {
+        let _t = trace_init();
+        time::pause();
+
+        let remaining = Arc::new(AtomicU64::new(0));
+        let rate_limit = RateLimitWithBurstLayer::new(
+            1,
+            Duration::from_millis(100),
+            2,
+            Duration::from_millis(400),
+        )
+        .with_remaining_gauge(remaining.clone());
+        let (mut service, mut handle) = mock::spawn_layer(rate_limit);
+
+        // Starts out with both buckets full.
+        assert_eq!(remaining.load(Ordering::Relaxed), 3);
+
+        assert_ready_ok!(service.poll_ready());
+        let response = service.call("hello 1");
+        assert_request_eq!(handle, "hello 1").send_response("world 1");
+        response.await.unwrap();
+
+        assert_eq!(remaining.load(Ordering::Relaxed), 2);
+    }
 }
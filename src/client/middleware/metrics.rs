@@ -0,0 +1,109 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Poll},
+};
+
+use hyper::{Request, Response};
+use tokio::time::Instant;
+
+use crate::metrics::Metrics;
+
+#[derive(Debug, Clone)]
+pub struct RecordMetricsLayer {
+    metrics: Arc<Metrics>,
+}
+
+impl RecordMetricsLayer {
+    pub fn new(metrics: Arc<Metrics>) -> Self {
+        Self { metrics }
+    }
+}
+
+impl<S> tower_layer::Layer<S> for RecordMetricsLayer {
+    type Service = RecordMetrics<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RecordMetrics {
+            inner,
+            metrics: self.metrics.clone(),
+            waiting_since: None,
+        }
+    }
+}
+
+/// Records requests, their outcome, and the time spent
+/// waiting for the inner service to become ready.
+///
+/// This should wrap the rate limiter, so that the waiting
+/// time reflects how long requests were held back.
+#[derive(Debug)]
+pub struct RecordMetrics<S> {
+    inner: S,
+    metrics: Arc<Metrics>,
+    waiting_since: Option<Instant>,
+}
+
+impl<S, B, ResBody> tower_service::Service<Request<B>> for RecordMetrics<S>
+where
+    S: tower_service::Service<Request<B>, Response = Response<ResBody>>,
+    S::Future: Unpin,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        match self.inner.poll_ready(cx) {
+            Poll::Pending => {
+                self.waiting_since.get_or_insert_with(Instant::now);
+                Poll::Pending
+            }
+            Poll::Ready(res) => {
+                if let Some(since) = self.waiting_since.take() {
+                    self.metrics.record_rate_limit_wait(since.elapsed());
+                }
+                Poll::Ready(res)
+            }
+        }
+    }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        let path = req.uri().path().to_string();
+        self.metrics.record_request(req.method(), &path);
+
+        ResponseFuture {
+            inner: self.inner.call(req),
+            metrics: self.metrics.clone(),
+            path,
+        }
+    }
+}
+
+pub struct ResponseFuture<F> {
+    inner: F,
+    metrics: Arc<Metrics>,
+    path: String,
+}
+
+impl<F, ResBody, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<Response<ResBody>, E>> + Unpin,
+{
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+        let res = ready!(Pin::new(&mut self.inner).poll(cx));
+
+        match &res {
+            Ok(res) => self.metrics.record_response(&self.path, res.status()),
+            Err(_) => self.metrics.record_transport_error(),
+        }
+
+        Poll::Ready(res)
+    }
+}
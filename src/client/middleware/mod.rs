@@ -1,7 +1,11 @@
 mod base_url;
 mod extra_headers;
 mod limit;
+mod metrics;
+mod token;
 
 pub use base_url::{BaseUrl, BaseUrlLayer};
 pub use extra_headers::{ExtraHeaders, ExtraHeadersLayer};
 pub use limit::{RateLimitWithBurst, RateLimitWithBurstLayer};
+pub use metrics::{RecordMetrics, RecordMetricsLayer};
+pub use token::{BearerToken, BearerTokenLayer, SharedToken};
@@ -0,0 +1,58 @@
+use std::sync::{Arc, RwLock};
+
+use hyper::{header, header::HeaderValue, Request};
+
+/// The bearer token of the agent, which can be replaced while the client is in use.
+pub type SharedToken = Arc<RwLock<Option<HeaderValue>>>;
+
+#[derive(Debug, Clone)]
+pub struct BearerTokenLayer {
+    token: SharedToken,
+}
+
+impl BearerTokenLayer {
+    pub fn new(token: SharedToken) -> Self {
+        BearerTokenLayer { token }
+    }
+}
+
+impl<S> tower_layer::Layer<S> for BearerTokenLayer {
+    type Service = BearerToken<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        BearerToken {
+            inner,
+            token: self.token.clone(),
+        }
+    }
+}
+
+/// Adds the current token as `Authorization` header, if there is one.
+#[derive(Debug, Clone)]
+pub struct BearerToken<S> {
+    inner: S,
+    token: SharedToken,
+}
+
+impl<S, B> tower_service::Service<Request<B>> for BearerToken<S>
+where
+    S: tower_service::Service<Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<B>) -> Self::Future {
+        if let Some(token) = self.token.read().unwrap().clone() {
+            req.headers_mut().insert(header::AUTHORIZATION, token);
+        }
+        self.inner.call(req)
+    }
+}
@@ -1,5 +1,7 @@
 use std::{
+    future::Future,
     ops::{Deref, DerefMut},
+    path::Path,
     sync::Arc,
     time::Duration,
 };
@@ -8,26 +10,28 @@ use anyhow::anyhow;
 use http_body_util::{BodyExt, Full};
 use hyper::{
     body::{Buf, Bytes},
-    header, Method, Request, StatusCode, Uri,
+    header::{self, HeaderValue},
+    Method, Request, Response, StatusCode, Uri,
 };
+use tokio::io::AsyncWriteExt;
 use tower::{Service, ServiceBuilder, ServiceExt};
-use tower_http::auth::{AddAuthorization, AddAuthorizationLayer};
 use tracing::{event, instrument, Level};
 
+use crate::metrics::Metrics;
 use crate::model::{
-    Agent, ApiResponse, ApiResponseData, ApiStatus, CargoTransfer, Chart, Construction, Contract,
-    Cooldown, DeliverCargo, Destination, Extraction, Faction, FactionSymbol, FlightMode, JumpGate,
-    Market, MarketTransaction, Meta, ModifyMount, MountType, Produce, RegisterAgent,
-    RegisterAgentSuccess, ScannedShip, ScannedSystem, ScannedWaypoint, Ship, ShipCargo,
-    ShipConditionEvent, ShipFuel, ShipModificationTransaction, ShipMount, ShipNav,
+    Agent, ApiErrorResponse, ApiResponse, ApiResponseData, ApiStatus, CargoTransfer, Chart,
+    Construction, Contract, Cooldown, DeliverCargo, Destination, Extraction, Faction,
+    FactionSymbol, FlightMode, JumpGate, Market, MarketTransaction, Meta, ModifyMount, MountType,
+    Produce, RegisterAgent, RegisterAgentSuccess, ScannedShip, ScannedSystem, ScannedWaypoint,
+    Ship, ShipCargo, ShipConditionEvent, ShipFuel, ShipModificationTransaction, ShipMount, ShipNav,
     ShipNavFlightMode, ShipPurchase, ShipRefuel, ShipTransaction, ShipType, Shipyard,
     ShipyardTransaction, Siphon, Survey, System, TradeGoodAmount, TradeSymbol, Waypoint,
     WaypointTraitSymbol, WaypointType,
 };
 use inner::InnerClient;
 use middleware::{
-    BaseUrl, BaseUrlLayer, ExtraHeaders, ExtraHeadersLayer, RateLimitWithBurst,
-    RateLimitWithBurstLayer,
+    BaseUrl, BaseUrlLayer, BearerToken, BearerTokenLayer, ExtraHeaders, ExtraHeadersLayer,
+    RateLimitWithBurst, RateLimitWithBurstLayer, RecordMetrics, RecordMetricsLayer, SharedToken,
 };
 
 mod inner;
@@ -39,25 +43,28 @@ const RATELIMIT_REQUESTS_BURST: u64 = 30;
 const RATELIMIT_DURATION_BURST: Duration = Duration::from_secs(60);
 
 type ClientStack =
-    RateLimitWithBurst<AddAuthorization<ExtraHeaders<BaseUrl<InnerClient<Full<Bytes>>>>>>;
+    RecordMetrics<RateLimitWithBurst<BearerToken<ExtraHeaders<BaseUrl<InnerClient<Full<Bytes>>>>>>>;
 
 #[derive(Debug)]
 struct WrappedClient(ClientStack);
 
 impl WrappedClient {
-    async fn new(base_url: &str) -> Result<Self, anyhow::Error> {
+    async fn new(
+        base_url: &str,
+        metrics: Arc<Metrics>,
+        token: SharedToken,
+    ) -> Result<Self, anyhow::Error> {
         let base_url = Uri::try_from(base_url)?;
         let client = InnerClient::new(base_url.clone()).await?;
+        let record_metrics = RecordMetricsLayer::new(metrics.clone());
         let rate_limit = RateLimitWithBurstLayer::new(
             RATELIMIT_REQUESTS_DEFAULT,
             RATELIMIT_DURATION_DEFAULT,
             RATELIMIT_REQUESTS_BURST,
             RATELIMIT_DURATION_BURST,
-        );
-        let token = std::env::var("SPACETRADERS_TOKEN")
-            .map_err(|err| event!(Level::ERROR, %err, "SPACETRADERS_TOKEN not found"))
-            .unwrap();
-        let auth = AddAuthorizationLayer::bearer(&token).as_sensitive(true);
+        )
+        .with_remaining_gauge(metrics.rate_limit_remaining());
+        let auth = BearerTokenLayer::new(token);
         let headers = Arc::new(vec![(
             header::USER_AGENT,
             "catfleet/0.1.0"
@@ -68,6 +75,7 @@ impl WrappedClient {
         let base_url = BaseUrlLayer::new(base_url);
 
         let service = ServiceBuilder::new()
+            .layer(record_metrics)
             .layer(rate_limit)
             .layer(auth)
             .layer(extra_headers)
@@ -92,24 +100,209 @@ impl DerefMut for WrappedClient {
     }
 }
 
+/// The interval between requests that uses `share` of the sustained rate limit.
+pub fn request_interval(share: f64) -> Duration {
+    let share = share.clamp(0.01, 1.0);
+    RATELIMIT_DURATION_DEFAULT.div_f64(RATELIMIT_REQUESTS_DEFAULT as f64 * share)
+}
+
+/// Download a file over HTTPS into `dest`, without buffering it in memory.
+/// This bypasses the rate limit and token of the [`Client`], so it should
+/// only be used for static files. Returns the number of bytes written.
+#[instrument(level = Level::DEBUG)]
+pub async fn download(url: &str, dest: &Path) -> Result<u64, anyhow::Error> {
+    let uri = Uri::try_from(url)?;
+    let mut client = InnerClient::<Full<Bytes>>::new(uri.clone()).await?;
+    let req = Request::builder()
+        .uri(uri)
+        .method(Method::GET)
+        .header(header::USER_AGENT, "catfleet/0.1.0")
+        .body(Full::<Bytes>::new(Bytes::new()))?;
+
+    let res = client.ready().await?.call(req).await?;
+    event!(Level::DEBUG, "Response status: {}", res.status());
+    if !res.status().is_success() {
+        return Err(anyhow!("Download failed with status {}", res.status()));
+    }
+
+    let mut file = tokio::fs::File::create(dest).await?;
+    let mut body = res.into_body();
+    let mut written = 0;
+    while let Some(frame) = body.frame().await {
+        if let Ok(data) = frame?.into_data() {
+            file.write_all(&data).await?;
+            written += data.len() as u64;
+        }
+    }
+    file.flush().await?;
+
+    Ok(written)
+}
+
+tokio::task_local! {
+    static TASK: String;
+}
+
+/// Run `fut` on behalf of the task called `name`.
+/// Observers can find out which task made a request through [`current_task`].
+pub async fn in_task<F: Future>(name: impl Into<String>, fut: F) -> F::Output {
+    TASK.scope(name.into(), fut).await
+}
+
+/// The name of the task the current request is made on behalf of, if any.
+pub fn current_task() -> Option<String> {
+    TASK.try_with(Clone::clone).ok()
+}
+
+/// Gets to see every response the [`Client`] successfully decodes,
+/// for example to keep metrics or caches up to date.
+pub trait Observer: std::fmt::Debug + Send + Sync {
+    /// `ship` is the symbol of the ship the request was about, if any.
+    fn observe(&self, ship: Option<&str>, data: &ApiResponseData);
+
+    /// Called when the API refuses to extract with a survey,
+    /// because it has expired or its deposits are exhausted.
+    fn survey_unusable(&self, _signature: &str) {}
+}
+
 #[derive(Debug)]
 pub struct Client {
     inner: WrappedClient,
+    token: SharedToken,
+    metrics: Arc<Metrics>,
+    observers: Vec<Arc<dyn Observer>>,
 }
 
 impl Client {
     #[instrument(level = Level::TRACE)]
     pub async fn new() -> Result<Self, anyhow::Error> {
-        let client = WrappedClient::new("https://api.spacetraders.io/v2/").await?;
-
-        Ok(Self { inner: client })
+        Self::new_with_url("https://api.spacetraders.io/v2/").await
     }
 
     #[instrument(level = Level::TRACE)]
     pub async fn new_with_url(url: &str) -> Result<Self, anyhow::Error> {
-        let client = WrappedClient::new(url).await?;
+        let metrics = Arc::new(Metrics::new());
+        let token = SharedToken::default();
+        let client = WrappedClient::new(url, metrics.clone(), token.clone()).await?;
+
+        let client = Self {
+            inner: client,
+            token,
+            observers: vec![metrics.clone()],
+            metrics,
+        };
+        match std::env::var("SPACETRADERS_TOKEN") {
+            Ok(token) => client.set_token(Some(&token))?,
+            Err(err) => event!(Level::WARN, %err, "SPACETRADERS_TOKEN not found"),
+        }
+
+        Ok(client)
+    }
+
+    /// Replace the token sent with every request, or stop sending one.
+    pub fn set_token(&self, token: Option<&str>) -> Result<(), anyhow::Error> {
+        let header = token
+            .map(|token| {
+                let mut header = HeaderValue::try_from(format!("Bearer {token}"))?;
+                header.set_sensitive(true);
+                Ok::<_, anyhow::Error>(header)
+            })
+            .transpose()?;
+        *self.token.write().unwrap() = header;
+
+        Ok(())
+    }
+
+    /// The metrics of this client.
+    pub fn metrics(&self) -> Arc<Metrics> {
+        self.metrics.clone()
+    }
+
+    /// Register an observer for all future responses.
+    pub fn add_observer(&mut self, observer: Arc<dyn Observer>) {
+        self.observers.push(observer);
+    }
+
+    fn observe(&self, ship: Option<&str>, data: &ApiResponseData) {
+        for observer in &self.observers {
+            observer.observe(ship, data);
+        }
+    }
+
+    fn survey_unusable(&self, signature: &str) {
+        event!(Level::DEBUG, "Survey `{signature}` can no longer be used");
+        for observer in &self.observers {
+            observer.survey_unusable(signature);
+        }
+    }
+
+    /// Send a request and decode the response, see [`Client::decode`].
+    async fn send(
+        &mut self,
+        req: Request<Full<Bytes>>,
+        ship: Option<&str>,
+    ) -> Result<Result<ApiResponse, serde_json::Error>, anyhow::Error> {
+        let res = self.inner.ready().await?.call(req).await?;
+        event!(Level::DEBUG, "Response status: {}", res.status());
+
+        let body = res.collect().await?.aggregate();
+
+        Ok(self.decode(body, ship))
+    }
+
+    /// Decode a response and pass it to the observers if that worked.
+    /// `ship` is the symbol of the ship the request was about, if any.
+    fn decode(&self, body: impl Buf, ship: Option<&str>) -> Result<ApiResponse, serde_json::Error> {
+        let json = serde_json::from_reader(body.reader());
+        if let Ok(ApiResponse { data, .. }) = &json {
+            self.observe(ship, data);
+        }
+        json
+    }
+
+    /// Send an arbitrary request through the client, sharing its rate limit
+    /// and token with everything else. The URI of `req` is relative to the
+    /// base URL of the API.
+    ///
+    /// Responses are passed to the observers as long as they can be decoded.
+    #[instrument(level = Level::DEBUG, skip(self, req), fields(req.method =% req.method(), req.uri =% req.uri()))]
+    pub async fn forward(
+        &mut self,
+        req: Request<Full<Bytes>>,
+    ) -> Result<Response<Full<Bytes>>, anyhow::Error> {
+        let ship = req
+            .uri()
+            .path()
+            .strip_prefix("/my/ships/")
+            .and_then(|rest| rest.split('/').next())
+            .filter(|ship| !ship.is_empty())
+            .map(str::to_string);
+        let survey = if req.uri().path().ends_with("/extract/survey") {
+            let body = req.body().clone().collect().await?.to_bytes();
+            serde_json::from_slice::<Survey>(&body).ok()
+        } else {
+            None
+        };
+
+        let res = self.inner.ready().await?.call(req).await?;
+        event!(Level::DEBUG, "Response status: {}", res.status());
+
+        let (parts, body) = res.into_parts();
+        let body = body.collect().await?.to_bytes();
+
+        if parts.status.is_success() {
+            if let Ok(ApiResponse { data, .. }) = serde_json::from_slice(&body) {
+                self.observe(ship.as_deref(), &data);
+            }
+        } else if let Some(survey) = survey {
+            if let Ok(ApiErrorResponse { error }) = serde_json::from_slice(&body) {
+                if error.is_survey_unusable() {
+                    self.survey_unusable(&survey.signature);
+                }
+            }
+        }
 
-        Ok(Self { inner: client })
+        Ok(Response::from_parts(parts, Full::new(body)))
     }
 
     #[instrument(level = Level::DEBUG, skip(self), err(Debug))]
@@ -159,12 +352,7 @@ impl Client {
             .header(header::CONTENT_TYPE, "application/json")
             .body(Full::<Bytes>::new(body.into()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, None).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::RegisterAgent(s)) => Ok(s),
@@ -185,12 +373,7 @@ impl Client {
             .method(Method::GET)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, None).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::GetAgent(agent)) => Ok(agent),
@@ -205,12 +388,7 @@ impl Client {
             .method(Method::GET)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, None).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::GetSystem(system)) => Ok(system),
@@ -236,12 +414,7 @@ impl Client {
             .method(Method::GET)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, None).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::GetWaypoint(waypoint)) => Ok(waypoint),
@@ -264,12 +437,7 @@ impl Client {
             .method(Method::GET)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, None).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::GetMarket(market)) => Ok(market),
@@ -295,12 +463,7 @@ impl Client {
             .method(Method::GET)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, None).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::GetShipyard(shipyard)) => Ok(shipyard),
@@ -326,12 +489,7 @@ impl Client {
             .method(Method::GET)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, None).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::GetJumpGate(gate)) => Ok(gate),
@@ -357,12 +515,7 @@ impl Client {
             .method(Method::GET)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, None).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::GetConstructionSite(construction)) => Ok(construction),
@@ -384,12 +537,7 @@ impl Client {
             .method(Method::GET)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader());
+        let json = self.send(req, None).await?;
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponse {
@@ -417,12 +565,7 @@ impl Client {
             .method(Method::GET)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader());
+        let json = self.send(req, None).await?;
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponse {
@@ -450,12 +593,7 @@ impl Client {
             .method(Method::GET)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader());
+        let json = self.send(req, None).await?;
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponse {
@@ -496,12 +634,7 @@ impl Client {
             .method(Method::GET)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader());
+        let json = self.send(req, None).await?;
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponse {
@@ -522,12 +655,7 @@ impl Client {
             .method(Method::GET)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, None).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::GetAgent(agent)) => Ok(agent),
@@ -549,12 +677,7 @@ impl Client {
             .method(Method::GET)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader());
+        let json = self.send(req, None).await?;
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponse {
@@ -568,6 +691,22 @@ impl Client {
         }
     }
 
+    /// List all of our contracts, fetching as many pages as necessary.
+    #[instrument(level = Level::DEBUG, skip(self))]
+    pub async fn list_all_contracts(&mut self) -> Result<Vec<Contract>, anyhow::Error> {
+        let mut contracts = Vec::new();
+        for page in 1.. {
+            let (mut list, meta) = self.list_contracts(Some(20), Some(page)).await?;
+            let done = list.is_empty() || page * u64::from(meta.limit) >= meta.total;
+            contracts.append(&mut list);
+            if done {
+                break;
+            }
+        }
+
+        Ok(contracts)
+    }
+
     #[instrument(level = Level::DEBUG, skip(self))]
     pub async fn get_contract(&mut self, contract_id: String) -> Result<Contract, anyhow::Error> {
         let req = Request::builder()
@@ -575,12 +714,7 @@ impl Client {
             .method(Method::GET)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, None).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::GetContract(contract)) => Ok(contract),
@@ -598,12 +732,7 @@ impl Client {
             .method(Method::POST)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, None).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::UpdateContract {
@@ -625,12 +754,7 @@ impl Client {
             .method(Method::POST)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, None).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::UpdateContract {
@@ -651,7 +775,7 @@ impl Client {
         amount: u64,
     ) -> Result<(ShipCargo, Contract), anyhow::Error> {
         let delivery = DeliverCargo {
-            ship_symbol: ship,
+            ship_symbol: ship.clone(),
             trade_symbol: cargo,
             units: amount,
         };
@@ -667,12 +791,7 @@ impl Client {
             .header(header::CONTENT_TYPE, "application/json")
             .body(Full::<Bytes>::new(body.into()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::UpdateContract {
@@ -691,12 +810,7 @@ impl Client {
             .method(Method::GET)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, None).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::GetFaction(faction)) => Ok(faction),
@@ -718,7 +832,7 @@ impl Client {
                 .ok_or_else(|| anyhow!("Invalid waypoint symbol"))?,
         );
         let delivery = DeliverCargo {
-            ship_symbol: ship,
+            ship_symbol: ship.clone(),
             trade_symbol: cargo,
             units: amount,
         };
@@ -736,12 +850,7 @@ impl Client {
             .header(header::CONTENT_TYPE, "application/json")
             .body(Full::<Bytes>::new(body.into()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::UpdateConstruction {
@@ -766,12 +875,7 @@ impl Client {
             .method(Method::GET)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader());
+        let json = self.send(req, None).await?;
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponse {
@@ -785,6 +889,22 @@ impl Client {
         }
     }
 
+    /// List all of our ships, fetching as many pages as necessary.
+    #[instrument(level = Level::DEBUG, skip(self))]
+    pub async fn list_all_ships(&mut self) -> Result<Vec<Ship>, anyhow::Error> {
+        let mut ships = Vec::new();
+        for page in 1.. {
+            let (mut list, meta) = self.list_ships(Some(20), Some(page)).await?;
+            let done = list.is_empty() || page * u64::from(meta.limit) >= meta.total;
+            ships.append(&mut list);
+            if done {
+                break;
+            }
+        }
+
+        Ok(ships)
+    }
+
     #[instrument(level = Level::DEBUG, skip(self))]
     pub async fn get_ship(&mut self, ship: String) -> Result<Box<Ship>, anyhow::Error> {
         let req = Request::builder()
@@ -792,12 +912,7 @@ impl Client {
             .method(Method::GET)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::GetShip(ship)) => Ok(ship),
@@ -812,12 +927,7 @@ impl Client {
             .method(Method::GET)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::GetCargo(cargo)) => Ok(cargo),
@@ -832,12 +942,7 @@ impl Client {
             .method(Method::GET)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::GetNav(nav)) => Ok(nav),
@@ -852,12 +957,7 @@ impl Client {
             .method(Method::GET)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::GetMounts(mounts)) => Ok(mounts),
@@ -872,12 +972,7 @@ impl Client {
             .method(Method::GET)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::GetShipTransaction { transaction }) => Ok(transaction),
@@ -895,12 +990,7 @@ impl Client {
             .method(Method::GET)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::GetShipTransaction { transaction }) => Ok(transaction),
@@ -930,7 +1020,7 @@ impl Client {
 
         let body = res.collect().await?.aggregate();
 
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.decode(body, Some(&ship)).map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::GetCooldown(cooldown)) => Ok(Some(cooldown)),
@@ -960,12 +1050,7 @@ impl Client {
             .header(header::CONTENT_TYPE, "application/json")
             .body(Full::<Bytes>::new(body.into()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, None).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::ShipPurchase {
@@ -984,12 +1069,7 @@ impl Client {
             .method(Method::POST)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::GetNav(nav)) => Ok(nav),
@@ -1024,12 +1104,7 @@ impl Client {
             .header(header::CONTENT_TYPE, "application/json")
             .body(Full::<Bytes>::new(body.into()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::Refine {
@@ -1049,12 +1124,7 @@ impl Client {
             .method(Method::POST)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::CreateChart { chart, waypoint }) => Ok((chart, waypoint)),
@@ -1069,12 +1139,7 @@ impl Client {
             .method(Method::POST)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::GetNav(nav)) => Ok(nav),
@@ -1092,12 +1157,7 @@ impl Client {
             .method(Method::POST)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::CreateSurvey { cooldown, surveys }) => Ok((cooldown, surveys)),
@@ -1115,12 +1175,7 @@ impl Client {
             .method(Method::POST)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::ExtractResources {
@@ -1143,12 +1198,7 @@ impl Client {
             .method(Method::POST)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::SiphonResources {
@@ -1181,9 +1231,23 @@ impl Client {
         let res = self.inner.ready().await?.call(req).await?;
         event!(Level::DEBUG, "Response status: {}", res.status());
 
+        let status = res.status();
         let body = res.collect().await?.aggregate();
 
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        // Callers need to know whether the survey can still be used.
+        if !status.is_success() {
+            return match serde_json::from_reader(body.reader()) {
+                Ok(ApiErrorResponse { error }) => {
+                    if error.is_survey_unusable() {
+                        self.survey_unusable(&survey.signature);
+                    }
+                    Err(error.into())
+                }
+                Err(_) => Err(anyhow!("Extraction failed with status {status}")),
+            };
+        }
+
+        let json = self.decode(body, Some(&ship)).map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::ExtractResources {
@@ -1213,15 +1277,10 @@ impl Client {
             .header(header::CONTENT_TYPE, "application/json")
             .body(Full::<Bytes>::new(body.into()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
-            Ok(ApiResponseData::GetCargo(cargo)) => Ok(cargo),
+            Ok(ApiResponseData::UpdateCargo { cargo, .. }) => Ok(cargo),
             Ok(d) => Err(anyhow!("Unexpected response data: {d:?}")),
         }
     }
@@ -1247,12 +1306,7 @@ impl Client {
             .header(header::CONTENT_TYPE, "application/json")
             .body(Full::<Bytes>::new(body.into()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::JumpShip {
@@ -1286,12 +1340,7 @@ impl Client {
             .header(header::CONTENT_TYPE, "application/json")
             .body(Full::<Bytes>::new(body.into()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::NavigateShip { fuel, nav, events }) => Ok((fuel, nav, events)),
@@ -1320,15 +1369,14 @@ impl Client {
             .header(header::CONTENT_TYPE, "application/json")
             .body(Full::<Bytes>::new(body.into()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
-            Ok(ApiResponseData::WarpShip { fuel, nav }) => Ok((fuel, nav)),
+            // Warps that report events decode as navigation, which comes first.
+            Ok(
+                ApiResponseData::WarpShip { fuel, nav }
+                | ApiResponseData::NavigateShip { fuel, nav, .. },
+            ) => Ok((fuel, nav)),
             Ok(d) => Err(anyhow!("Unexpected response data: {d:?}")),
         }
     }
@@ -1352,12 +1400,7 @@ impl Client {
             .header(header::CONTENT_TYPE, "application/json")
             .body(Full::<Bytes>::new(body.into()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::GetNav(nav)) => Ok(nav),
@@ -1382,12 +1425,7 @@ impl Client {
             .header(header::CONTENT_TYPE, "application/json")
             .body(Full::<Bytes>::new(body.into()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::MarketTransaction {
@@ -1409,12 +1447,7 @@ impl Client {
             .method(Method::POST)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::ScanSystems { cooldown, systems }) => Ok((cooldown, systems)),
@@ -1432,12 +1465,7 @@ impl Client {
             .method(Method::POST)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::ScanWaypoints {
@@ -1458,12 +1486,7 @@ impl Client {
             .method(Method::POST)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::ScanShips { cooldown, ships }) => Ok((cooldown, ships)),
@@ -1491,12 +1514,7 @@ impl Client {
             .header(header::CONTENT_TYPE, "application/json")
             .body(Full::<Bytes>::new(body.into()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::RefuelShip {
@@ -1525,12 +1543,7 @@ impl Client {
             .header(header::CONTENT_TYPE, "application/json")
             .body(Full::<Bytes>::new(body.into()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::MarketTransaction {
@@ -1552,7 +1565,7 @@ impl Client {
         let transfer = CargoTransfer {
             trade_symbol: cargo.trade_symbol,
             units: cargo.units,
-            ship_symbol: ship.clone(),
+            ship_symbol: target_ship,
         };
 
         let body = match serde_json::to_vec(&transfer) {
@@ -1566,15 +1579,10 @@ impl Client {
             .header(header::CONTENT_TYPE, "application/json")
             .body(Full::<Bytes>::new(body.into()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
-            Ok(ApiResponseData::GetCargo(cargo)) => Ok(cargo),
+            Ok(ApiResponseData::UpdateCargo { cargo, .. }) => Ok(cargo),
             Ok(d) => Err(anyhow!("Unexpected response data: {d:?}")),
         }
     }
@@ -1586,15 +1594,14 @@ impl Client {
             .method(Method::POST)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
-            Ok(ApiResponseData::NegotiateContract { contract }) => Ok(contract),
+            // `{ contract }` decodes as the update of a contract, which comes first.
+            Ok(
+                ApiResponseData::NegotiateContract { contract }
+                | ApiResponseData::UpdateContract { contract, .. },
+            ) => Ok(contract),
             Ok(d) => Err(anyhow!("Unexpected response data: {d:?}")),
         }
     }
@@ -1626,12 +1633,7 @@ impl Client {
             .header(header::CONTENT_TYPE, "application/json")
             .body(Full::<Bytes>::new(body.into()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::ModifyMount {
@@ -1671,12 +1673,7 @@ impl Client {
             .header(header::CONTENT_TYPE, "application/json")
             .body(Full::<Bytes>::new(body.into()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::ModifyMount {
@@ -1699,12 +1696,7 @@ impl Client {
             .method(Method::POST)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::ScrapShip { agent, transaction }) => Ok((agent, transaction)),
@@ -1722,12 +1714,7 @@ impl Client {
             .method(Method::POST)
             .body(Full::<Bytes>::new(Bytes::new()))?;
 
-        let res = self.inner.ready().await?.call(req).await?;
-        event!(Level::DEBUG, "Response status: {}", res.status());
-
-        let body = res.collect().await?.aggregate();
-
-        let json = serde_json::from_reader(body.reader()).map(|res: ApiResponse| res.data);
+        let json = self.send(req, Some(&ship)).await?.map(|res| res.data);
         match json {
             Err(e) => Err(anyhow!(e)),
             Ok(ApiResponseData::RepairShip {
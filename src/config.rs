@@ -0,0 +1,314 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use anyhow::Context;
+use serde::Deserialize;
+use tracing::{event, instrument, Level};
+
+use crate::{
+    fleet::BehaviorConfig,
+    model::{FactionSymbol, ShipRole, ShipType},
+    server::Role,
+};
+
+/// The environment variable that can be used to override
+/// the location of the configuration file.
+const CONFIG_PATH_VAR: &str = "CATFLEET_CONFIG";
+const CONFIG_PATH_DEFAULT: &str = "catfleet.toml";
+
+/// Runtime configuration of catfleet, read from a TOML file.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Config {
+    /// Configuration of the dashboard server.
+    pub server: ServerConfig,
+    /// Configuration of the persistent store.
+    pub store: StoreConfig,
+    /// Configuration of the universe crawl.
+    pub crawl: CrawlConfig,
+    /// Configuration of the reset watcher.
+    pub reset: ResetConfig,
+    /// Configuration of contract tracking.
+    pub contracts: ContractsConfig,
+    /// Configuration of the agent and leaderboard history.
+    pub history: HistoryConfig,
+    /// Configuration of the fleet runtime.
+    pub fleet: FleetConfig,
+    /// Configuration of automatic ship purchases.
+    pub expansion: ExpansionConfig,
+    /// The agent to register after the universe was reset.
+    pub agent: Option<AgentConfig>,
+}
+
+impl Config {
+    /// Load the configuration from the file pointed to by `CATFLEET_CONFIG`,
+    /// or `catfleet.toml` in the working directory if the variable is unset.
+    ///
+    /// A missing file is not an error; the defaults are used instead.
+    #[instrument(level = Level::DEBUG)]
+    pub fn load() -> Result<Self, anyhow::Error> {
+        let path = std::env::var_os(CONFIG_PATH_VAR)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(CONFIG_PATH_DEFAULT));
+
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                event!(
+                    Level::WARN,
+                    "Config file `{}` not found; using defaults",
+                    path.display()
+                );
+                return Ok(Self::default());
+            }
+            Err(err) => return Err(err).with_context(|| format!("Reading `{}`", path.display())),
+        };
+
+        toml::from_str(&contents).with_context(|| format!("Parsing `{}`", path.display()))
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// The address the server listens on.
+    pub address: String,
+    /// Authentication of clients talking to the server.
+    pub auth: AuthConfig,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            address: "127.0.0.1:3000".to_string(),
+            auth: AuthConfig::default(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct StoreConfig {
+    /// The SQLite database file, created if it does not exist.
+    pub path: PathBuf,
+}
+
+impl Default for StoreConfig {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("catfleet.db"),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct CrawlConfig {
+    /// Whether to crawl all systems and waypoints in the background
+    /// until the local map is complete.
+    pub enabled: bool,
+    /// The share of the sustained rate limit the crawl may use, between 0 and 1.
+    pub rate_share: f64,
+    /// A `systems.json` snapshot to seed an empty map with, as an HTTPS URL or a path.
+    /// Listing the systems page by page is skipped if this succeeds.
+    pub seed: Option<String>,
+}
+
+impl Default for CrawlConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rate_share: 0.25,
+            seed: Some("https://api.spacetraders.io/v2/systems.json".to_string()),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct ResetConfig {
+    /// Whether to watch for resets of the universe.
+    pub enabled: bool,
+    /// How often to ask the API for the date of the last reset, in seconds.
+    pub poll_interval_secs: u64,
+}
+
+impl Default for ResetConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            poll_interval_secs: 10 * 60,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct ContractsConfig {
+    /// Whether to keep contracts in sync and warn about their deadlines.
+    pub enabled: bool,
+    /// How often to list our contracts, in seconds.
+    pub poll_interval_secs: u64,
+    /// How long before a deadline to warn about it, in seconds.
+    pub warn_before_secs: u64,
+}
+
+impl Default for ContractsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            poll_interval_secs: 5 * 60,
+            warn_before_secs: 6 * 60 * 60,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct HistoryConfig {
+    /// Whether to sample our agent and the leaderboards periodically.
+    pub enabled: bool,
+    /// How often to take a sample, in seconds.
+    pub interval_secs: u64,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 15 * 60,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct FleetConfig {
+    /// Whether to drive our ships automatically.
+    pub enabled: bool,
+    /// How often to list our ships to pick up new ones, in seconds.
+    pub poll_interval_secs: u64,
+    /// The behavior of each ship, by symbol.
+    pub ships: HashMap<String, BehaviorConfig>,
+    /// The behavior of ships that are not listed in `ships`, by their role,
+    /// so that new ships get to work without editing the config.
+    /// Ships that are listed in neither idle.
+    pub roles: HashMap<ShipRole, BehaviorConfig>,
+}
+
+impl Default for FleetConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            poll_interval_secs: 60,
+            ships: HashMap::new(),
+            roles: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct ExpansionConfig {
+    /// Whether to plan ship purchases periodically.
+    pub enabled: bool,
+    /// Only report what would be bought, without buying anything.
+    pub dry_run: bool,
+    /// How often to plan, in seconds.
+    pub interval_secs: u64,
+    /// Credits that are always left after a purchase.
+    pub reserve: u64,
+    /// Ships are only bought if they are projected to pay for themselves within this many hours.
+    pub max_payback_hours: f64,
+    /// How many hours of transactions the income of our ships is judged by.
+    pub lookback_hours: u64,
+    /// The types of ships to consider buying.
+    pub ship_types: Vec<ShipType>,
+}
+
+impl Default for ExpansionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dry_run: true,
+            interval_secs: 60 * 60,
+            reserve: 100_000,
+            max_payback_hours: 24.0,
+            lookback_hours: 24,
+            ship_types: vec![
+                ShipType::ShipMiningDrone,
+                ShipType::ShipLightHauler,
+                ShipType::ShipProbe,
+            ],
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AgentConfig {
+    /// The call sign of the agent, between 3 and 14 characters.
+    pub symbol: String,
+    pub faction: FactionSymbol,
+    pub email: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct AuthConfig {
+    /// The API keys that are allowed to access the server.
+    /// If this is empty, every authenticated route is rejected.
+    pub api_keys: Vec<ApiKey>,
+}
+
+/// A named API key and the role it grants.
+#[derive(Deserialize, Debug, Clone)]
+pub struct ApiKey {
+    /// A human readable name for the key, used in logs.
+    pub name: String,
+    /// The secret that clients send as a bearer token.
+    pub key: String,
+    pub role: Role,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_api_keys() {
+        let config: Config = toml::from_str(
+            r#"
+            [server]
+            address = "0.0.0.0:8080"
+
+            [[server.auth.api_keys]]
+            name = "dashboard"
+            key = "hunter2"
+            role = "read_only"
+
+            [[server.auth.api_keys]]
+            name = "scripts"
+            key = "correct-horse"
+            role = "operator"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.server.address, "0.0.0.0:8080");
+        assert_eq!(config.server.auth.api_keys.len(), 2);
+        assert_eq!(config.server.auth.api_keys[0].role, Role::ReadOnly);
+        assert_eq!(config.server.auth.api_keys[1].role, Role::Operator);
+    }
+
+    #[test]
+    fn empty_file_uses_defaults() {
+        let config: Config = toml::from_str("").unwrap();
+
+        assert_eq!(config.server.address, "127.0.0.1:3000");
+        assert!(config.server.auth.api_keys.is_empty());
+        assert_eq!(config.store.path, PathBuf::from("catfleet.db"));
+        assert!(config.crawl.enabled);
+        assert!(!config.fleet.enabled);
+        assert!(config.expansion.dry_run);
+        assert!(config.agent.is_none());
+    }
+}
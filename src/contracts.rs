@@ -0,0 +1,304 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::{
+    sync::Mutex,
+    time::{interval, MissedTickBehavior},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{event, instrument, Level};
+use utoipa::ToSchema;
+
+use crate::{
+    client::Client,
+    config::ContractsConfig,
+    events::{Event, Events},
+    model::{Contract, TradeSymbol},
+    store::Store,
+};
+
+/// Which deadline of a contract is meant.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum Deadline {
+    /// The contract has to be accepted by then.
+    Accept,
+    /// The accepted contract has to be fulfilled by then.
+    Fulfill,
+}
+
+/// What is left to deliver of one good.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Delivery {
+    pub trade_symbol: TradeSymbol,
+    pub destination_symbol: String,
+    pub units_remaining: u64,
+    /// The lowest price the good was last seen for at a market in the destination's system.
+    pub unit_price: Option<u64>,
+    /// What buying the remaining units would cost, if the price is known.
+    pub cost: Option<u64>,
+}
+
+/// A contract along with how far along it is and what it is worth.
+#[derive(Serialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractStatus {
+    pub contract: Contract,
+    pub deliveries: Vec<Delivery>,
+    pub units_remaining: u64,
+    /// The credits the contract will still pay out.
+    pub payment_remaining: u64,
+    /// What buying all remaining units would cost.
+    /// Unknown if the price of any of the goods is unknown.
+    pub estimated_cost: Option<u64>,
+    /// The remaining payment minus the estimated cost.
+    pub estimated_profit: Option<i64>,
+    /// The deadline that matters now: to accept an offer, or to fulfill an accepted contract.
+    /// Fulfilled contracts have none.
+    pub deadline: Option<Deadline>,
+    /// When that deadline is.
+    pub deadline_at: Option<String>,
+    /// Seconds until that deadline, negative once it passed.
+    pub seconds_left: Option<i64>,
+}
+
+impl ContractStatus {
+    /// `prices` maps system symbols to the cheapest purchase price of each good in the system.
+    fn new(
+        contract: Contract,
+        prices: &HashMap<String, HashMap<TradeSymbol, u64>>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let deliveries: Vec<_> = contract
+            .terms
+            .deliver
+            .iter()
+            .flatten()
+            .map(|good| {
+                let units_remaining = good.units_required.saturating_sub(good.units_fulfilled);
+                let unit_price = prices
+                    .get(system_of(&good.destination_symbol))
+                    .and_then(|prices| prices.get(&good.trade_symbol))
+                    .copied();
+                Delivery {
+                    trade_symbol: good.trade_symbol,
+                    destination_symbol: good.destination_symbol.clone(),
+                    units_remaining,
+                    unit_price,
+                    cost: unit_price.map(|price| price * units_remaining),
+                }
+            })
+            .collect();
+
+        let units_remaining = deliveries.iter().map(|d| d.units_remaining).sum();
+        let estimated_cost = deliveries.iter().map(|d| d.cost).sum::<Option<u64>>();
+
+        let payment = &contract.terms.payment;
+        let payment_remaining = match (contract.accepted, contract.fulfilled) {
+            (_, true) => 0,
+            (true, false) => payment.on_fulfilled,
+            (false, false) => payment.on_accepted + payment.on_fulfilled,
+        };
+        let estimated_profit = estimated_cost.map(|cost| payment_remaining as i64 - cost as i64);
+
+        let deadline = match (contract.accepted, contract.fulfilled) {
+            (_, true) => None,
+            (true, false) => Some((Deadline::Fulfill, contract.terms.deadline.clone())),
+            (false, false) => Some((Deadline::Accept, contract.deadline_to_accept.clone())),
+        };
+        let seconds_left = deadline.as_ref().and_then(|(_, at)| {
+            DateTime::parse_from_rfc3339(at)
+                .ok()
+                .map(|at| (at.with_timezone(&Utc) - now).num_seconds())
+        });
+        let (deadline, deadline_at) = deadline.unzip();
+
+        Self {
+            contract,
+            deliveries,
+            units_remaining,
+            payment_remaining,
+            estimated_cost,
+            estimated_profit,
+            deadline,
+            deadline_at,
+            seconds_left,
+        }
+    }
+}
+
+fn system_of(waypoint_symbol: &str) -> &str {
+    waypoint_symbol
+        .rsplit_once('-')
+        .map_or(waypoint_symbol, |(system, _)| system)
+}
+
+/// The status of every stored contract, the most urgent first.
+pub fn statuses(store: &Store, now: DateTime<Utc>) -> Result<Vec<ContractStatus>, anyhow::Error> {
+    let contracts = store.all::<Contract>()?;
+
+    let mut prices = HashMap::new();
+    for contract in &contracts {
+        for good in contract.value.terms.deliver.iter().flatten() {
+            let system = system_of(&good.destination_symbol);
+            if !prices.contains_key(system) {
+                prices.insert(system.to_string(), store.cheapest_purchase_prices(system)?);
+            }
+        }
+    }
+
+    let mut statuses: Vec<_> = contracts
+        .into_iter()
+        .map(|contract| ContractStatus::new(contract.value, &prices, now))
+        .collect();
+    statuses.sort_by_key(|status| (status.seconds_left.is_none(), status.seconds_left));
+
+    Ok(statuses)
+}
+
+/// Keep our contracts in sync and warn whenever one of their deadlines approaches.
+///
+/// The contracts reach the store through the client's observers,
+/// so this only has to list them now and then.
+#[instrument(name = "contracts", level = Level::INFO, skip_all)]
+pub async fn run(
+    config: ContractsConfig,
+    client: Arc<Mutex<Client>>,
+    store: Arc<Store>,
+    events: Events,
+    shutdown: CancellationToken,
+) -> Result<(), anyhow::Error> {
+    let mut ticker = interval(Duration::from_secs(config.poll_interval_secs.max(1)));
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut alerted = HashSet::new();
+
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => return Ok(()),
+            _ = ticker.tick() => {}
+        }
+
+        if let Err(err) = client.lock().await.list_all_contracts().await {
+            event!(Level::WARN, "Failed to list contracts: {err:?}");
+        }
+
+        let statuses = match statuses(&store, Utc::now()) {
+            Ok(statuses) => statuses,
+            Err(err) => {
+                event!(Level::WARN, "Failed to read contracts: {err:?}");
+                continue;
+            }
+        };
+        for event in due_alerts(&statuses, config.warn_before_secs, &mut alerted) {
+            event!(Level::WARN, "Contract deadline approaching: {event:?}");
+            events.emit(event);
+        }
+    }
+}
+
+/// Alerts for deadlines that are at most `warn_before_secs` away
+/// and have not been alerted about before.
+fn due_alerts(
+    statuses: &[ContractStatus],
+    warn_before_secs: u64,
+    alerted: &mut HashSet<(String, Deadline)>,
+) -> Vec<Event> {
+    statuses
+        .iter()
+        .filter_map(|status| {
+            let deadline = status.deadline?;
+            let seconds_left = status.seconds_left?;
+            if !(0..=warn_before_secs as i64).contains(&seconds_left) {
+                return None;
+            }
+            if !alerted.insert((status.contract.id.clone(), deadline)) {
+                return None;
+            }
+            Some(Event::ContractDeadline {
+                contract_id: status.contract.id.clone(),
+                deadline,
+                deadline_at: status.deadline_at.clone()?,
+                seconds_left,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{fixtures, Market};
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2025-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn estimates_cost_and_profit_from_known_prices() {
+        let store = Store::open_in_memory().unwrap();
+        let market: Market = serde_json::from_value(serde_json::json!({
+            "symbol": "X1-A-2",
+            "exports": [],
+            "imports": [],
+            "exchange": [],
+            "tradeGoods": [{
+                "symbol": "IRON_ORE",
+                "type": "EXPORT",
+                "tradeVolume": 60,
+                "supply": "ABUNDANT",
+                "purchasePrice": 20,
+                "sellPrice": 15
+            }]
+        }))
+        .unwrap();
+        store.record_prices(&market).unwrap();
+        store
+            .put(&fixtures::contract(
+                "far",
+                "X1-A-1",
+                100,
+                "2025-01-02T00:00:00Z",
+            ))
+            .unwrap();
+        store
+            .put(&fixtures::contract(
+                "soon",
+                "X1-B-1",
+                10,
+                "2025-01-01T01:00:00Z",
+            ))
+            .unwrap();
+
+        let statuses = statuses(&store, now()).unwrap();
+
+        assert_eq!(statuses[0].contract.id, "soon");
+        assert_eq!(statuses[0].seconds_left, Some(3600));
+        assert_eq!(statuses[0].estimated_cost, None);
+
+        let far = &statuses[1];
+        assert_eq!(far.deadline, Some(Deadline::Fulfill));
+        assert_eq!(far.units_remaining, 100);
+        assert_eq!(far.payment_remaining, 10000);
+        assert_eq!(far.estimated_cost, Some(2000));
+        assert_eq!(far.estimated_profit, Some(8000));
+    }
+
+    #[test]
+    fn alerts_once_per_deadline() {
+        let contract = fixtures::contract("c", "X1-A-1", 10, "2025-01-01T01:00:00Z");
+        let status = ContractStatus::new(contract, &HashMap::new(), now());
+        let mut alerted = HashSet::new();
+
+        assert!(due_alerts(&[status.clone()], 1800, &mut alerted).is_empty());
+        assert_eq!(due_alerts(&[status.clone()], 7200, &mut alerted).len(), 1);
+        assert!(due_alerts(&[status], 7200, &mut alerted).is_empty());
+    }
+}
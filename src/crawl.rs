@@ -0,0 +1,221 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::{
+    sync::{broadcast, Mutex},
+    time::{interval, MissedTickBehavior},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{event, instrument, Level};
+
+use crate::{
+    client::{self, Client},
+    config::CrawlConfig,
+    events::{Event, Events},
+    import,
+    model::System,
+    store::Store,
+};
+
+/// The name the crawl saves its progress under.
+const JOB: &str = "crawl";
+/// The largest page size the API allows.
+const PAGE_SIZE: u64 = 20;
+/// How often listing a page of waypoints may fail before the system is skipped.
+/// Skipped systems are not marked as crawled, so the next crawl tries them again.
+const MAX_ATTEMPTS: u32 = 5;
+
+/// How far the crawl got. Systems are listed first, because listing
+/// the waypoints of each system requires knowing the system.
+///
+/// Which systems already had their waypoints listed is tracked by the store,
+/// so that a restart does not list them again.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", tag = "phase")]
+enum CrawlState {
+    Systems { next_page: u64 },
+    Waypoints,
+}
+
+/// Page through every system and waypoint of the universe, until the store knows all of them.
+/// After the universe was reset, the crawl starts over.
+#[instrument(name = "crawl", level = Level::INFO, skip_all)]
+pub async fn run(
+    config: CrawlConfig,
+    client: Arc<Mutex<Client>>,
+    store: Arc<Store>,
+    events: Events,
+    shutdown: CancellationToken,
+) -> Result<(), anyhow::Error> {
+    let mut events = events.subscribe();
+
+    loop {
+        tokio::select! {
+            res = crawl(&config, &client, &store, &shutdown) => {
+                res?;
+                tokio::select! {
+                    _ = shutdown.cancelled() => return Ok(()),
+                    _ = reset(&mut events) => {}
+                }
+            }
+            _ = reset(&mut events) => {}
+        }
+        event!(Level::INFO, "Restarting crawl after reset");
+    }
+}
+
+/// Resolves once the universe was reset.
+async fn reset(events: &mut broadcast::Receiver<Event>) {
+    loop {
+        match events.recv().await {
+            Ok(Event::Reset { .. }) => return,
+            Ok(_) => {}
+            // We might have missed a reset; starting over does no harm.
+            Err(broadcast::error::RecvError::Lagged(_)) => return,
+            Err(broadcast::error::RecvError::Closed) => std::future::pending().await,
+        }
+    }
+}
+
+/// Responses reach the store through the client's observers, so this only has
+/// to request the pages and record how far it got. Failed requests are retried.
+async fn crawl(
+    config: &CrawlConfig,
+    client: &Mutex<Client>,
+    store: &Arc<Store>,
+    shutdown: &CancellationToken,
+) -> Result<(), anyhow::Error> {
+    let mut state = match store.job_state(JOB)? {
+        Some(state) => state,
+        None => seed(config, store).await?,
+    };
+    event!(Level::INFO, ?state, "Resuming crawl");
+
+    let mut ticker = interval(client::request_interval(config.rate_share));
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    while let CrawlState::Systems { next_page } = state {
+        tokio::select! {
+            _ = shutdown.cancelled() => return Ok(()),
+            _ = ticker.tick() => {}
+        }
+
+        let res = client
+            .lock()
+            .await
+            .list_systems(Some(PAGE_SIZE), Some(next_page))
+            .await;
+        let (systems, meta) = match res {
+            Ok(res) => res,
+            Err(err) => {
+                event!(
+                    Level::WARN,
+                    "Failed to list systems page {next_page}: {err:?}"
+                );
+                continue;
+            }
+        };
+
+        state = if systems.is_empty() || next_page * PAGE_SIZE >= meta.total {
+            event!(Level::INFO, total = meta.total, "Listed all systems");
+            CrawlState::Waypoints
+        } else {
+            event!(
+                Level::DEBUG,
+                total = meta.total,
+                "Listed systems page {next_page}"
+            );
+            CrawlState::Systems {
+                next_page: next_page + 1,
+            }
+        };
+        store.set_job_state(JOB, &state)?;
+    }
+
+    for system in store.uncrawled_systems()? {
+        let symbol = system.symbol.0;
+        let mut page = 1;
+        let mut failures = 0;
+
+        while !system.waypoints.is_empty() {
+            tokio::select! {
+                _ = shutdown.cancelled() => return Ok(()),
+                _ = ticker.tick() => {}
+            }
+
+            let res = client
+                .lock()
+                .await
+                .list_waypoints(symbol.clone(), Some(PAGE_SIZE), Some(page), None, None)
+                .await;
+            match res {
+                Ok((waypoints, meta)) => {
+                    if waypoints.is_empty() || page * PAGE_SIZE >= meta.total {
+                        break;
+                    }
+                    page += 1;
+                    failures = 0;
+                }
+                Err(err) => {
+                    event!(
+                        Level::WARN,
+                        "Failed to list waypoints of `{symbol}` page {page}: {err:?}"
+                    );
+                    failures += 1;
+                    if failures >= MAX_ATTEMPTS {
+                        break;
+                    }
+                }
+            }
+        }
+
+        if failures >= MAX_ATTEMPTS {
+            event!(
+                Level::WARN,
+                "Skipping `{symbol}` after {failures} failed attempts"
+            );
+            continue;
+        }
+        store.mark_system_crawled(&symbol)?;
+        event!(Level::DEBUG, "Crawled waypoints of `{symbol}`");
+    }
+
+    event!(Level::INFO, "Crawl complete");
+    Ok(())
+}
+
+/// Import the configured snapshot into an empty map,
+/// so that only the waypoints are left to crawl.
+async fn seed(config: &CrawlConfig, store: &Arc<Store>) -> Result<CrawlState, anyhow::Error> {
+    let state = match &config.seed {
+        Some(source) if store.count::<System>()? == 0 => {
+            match import::import_systems(source, store.clone()).await {
+                Ok(_) => CrawlState::Waypoints,
+                Err(err) => {
+                    event!(Level::WARN, "Failed to seed the map: {err:?}");
+                    CrawlState::Systems { next_page: 1 }
+                }
+            }
+        }
+        _ => CrawlState::Systems { next_page: 1 },
+    };
+
+    store.set_job_state(JOB, &state)?;
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_survives_a_round_trip_through_the_store() {
+        let store = Store::open_in_memory().unwrap();
+        assert_eq!(store.job_state::<CrawlState>(JOB).unwrap(), None);
+
+        let state = CrawlState::Systems { next_page: 42 };
+        store.set_job_state(JOB, &state).unwrap();
+
+        assert_eq!(store.job_state(JOB).unwrap(), Some(state));
+    }
+}
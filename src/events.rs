@@ -0,0 +1,50 @@
+use serde::Serialize;
+use tokio::sync::broadcast;
+use utoipa::ToSchema;
+
+use crate::contracts::Deadline;
+
+/// How many events a slow subscriber may fall behind before it misses some.
+const CAPACITY: usize = 64;
+
+/// Something happened that the dashboard and other tasks should know about.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum Event {
+    /// The universe was reset. The data of the previous reset was archived.
+    #[serde(rename_all = "camelCase")]
+    Reset {
+        previous_reset_date: String,
+        reset_date: String,
+    },
+    /// A deadline of one of our contracts is approaching.
+    #[serde(rename_all = "camelCase")]
+    ContractDeadline {
+        contract_id: String,
+        deadline: Deadline,
+        deadline_at: String,
+        seconds_left: i64,
+    },
+}
+
+/// Broadcasts events to everyone who subscribed.
+#[derive(Debug, Clone)]
+pub struct Events {
+    sender: broadcast::Sender<Event>,
+}
+
+impl Events {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+        Self { sender }
+    }
+
+    pub fn emit(&self, event: Event) {
+        // Nobody listening is fine.
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.sender.subscribe()
+    }
+}
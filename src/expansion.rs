@@ -0,0 +1,335 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use chrono::Utc;
+use tokio::{
+    sync::Mutex,
+    time::{interval, MissedTickBehavior},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{event, instrument, Level};
+
+use crate::{
+    client::{self, Client},
+    config::ExpansionConfig,
+    model::{Ship, ShipRole, ShipType, Shipyard},
+    store::{LedgerQuery, ShipReport, Store},
+};
+
+/// Ships with a shorter track record than this count as having had this long,
+/// so that a single early sale does not make a type look like a gold mine.
+const MIN_HOURS: f64 = 1.0;
+
+const MILLIS_PER_HOUR: f64 = 60.0 * 60.0 * 1000.0;
+
+/// A ship for sale at a shipyard one of our ships is at.
+#[derive(Debug, Clone, PartialEq)]
+struct Offer {
+    ship_type: ShipType,
+    shipyard: String,
+    price: u64,
+}
+
+/// An offer along with what a ship like it earned us.
+#[derive(Debug, Clone, PartialEq)]
+struct Candidate {
+    offer: Offer,
+    /// The average net income per hour of our ships with the same role,
+    /// each over the part of the lookback it was active in.
+    income_per_hour: f64,
+    /// How long until the ship paid for itself.
+    payback_hours: f64,
+}
+
+/// The role ships of a type are registered with, which is how our ships are told apart.
+fn role(ship_type: ShipType) -> ShipRole {
+    match ship_type {
+        ShipType::ShipProbe => ShipRole::Satellite,
+        ShipType::ShipMiningDrone | ShipType::ShipSiphonDrone | ShipType::ShipOreHound => {
+            ShipRole::Excavator
+        }
+        ShipType::ShipInterceptor => ShipRole::Interceptor,
+        ShipType::ShipLightHauler | ShipType::ShipHeavyFreighter => ShipRole::Hauler,
+        ShipType::ShipCommandFrigate => ShipRole::Command,
+        ShipType::ShipExplorer => ShipRole::Explorer,
+        ShipType::ShipLightShuttle => ShipRole::Transport,
+        ShipType::ShipRefiningFreighter => ShipRole::Refinery,
+        ShipType::ShipSurveyor => ShipRole::Surveyor,
+    }
+}
+
+/// The cheapest offer of each type at the shipyards our ships are at.
+/// Ships can only be bought, and their prices only seen, with a ship present.
+fn offers(shipyards: &[Shipyard], ships: &[Ship]) -> Vec<Offer> {
+    let mut offers: HashMap<ShipType, Offer> = HashMap::new();
+    for shipyard in shipyards {
+        if !ships
+            .iter()
+            .any(|ship| ship.nav.waypoint_symbol == shipyard.symbol)
+        {
+            continue;
+        }
+        for listing in shipyard.ships.iter().flatten() {
+            let offer = Offer {
+                ship_type: listing.ship_type,
+                shipyard: shipyard.symbol.clone(),
+                price: listing.purchase_price,
+            };
+            offers
+                .entry(listing.ship_type)
+                .and_modify(|best| {
+                    if offer.price < best.price {
+                        *best = offer.clone();
+                    }
+                })
+                .or_insert(offer);
+        }
+    }
+    offers.into_values().collect()
+}
+
+/// The offers of the configured types that pay for themselves quickly enough,
+/// the quickest first. Types we own no ship of have no track record and are left out.
+///
+/// Each ship's net income is divided by the time since its first entry in the ledger
+/// within the lookback, so that ships bought recently are not averaged down.
+/// Ships without any entry count as having earned nothing over the whole lookback.
+fn plan(
+    config: &ExpansionConfig,
+    offers: &[Offer],
+    ships: &[Ship],
+    reports: &[ShipReport],
+    now: i64,
+) -> Vec<Candidate> {
+    let lookback = config.lookback_hours.max(1) as f64;
+    let income: HashMap<&str, f64> = reports
+        .iter()
+        .map(|report| {
+            let hours = (now - report.first_recorded_at) as f64 / MILLIS_PER_HOUR;
+            let hours = hours.clamp(MIN_HOURS, lookback.max(MIN_HOURS));
+            (report.ship_symbol.as_str(), report.net as f64 / hours)
+        })
+        .collect();
+
+    let mut candidates: Vec<_> = offers
+        .iter()
+        .filter(|offer| config.ship_types.contains(&offer.ship_type))
+        .filter_map(|offer| {
+            let role = role(offer.ship_type);
+            let peers: Vec<_> = ships
+                .iter()
+                .filter(|ship| ship.registration.role == role)
+                .collect();
+            if peers.is_empty() {
+                return None;
+            }
+            let total: f64 = peers
+                .iter()
+                .map(|ship| {
+                    income
+                        .get(ship.symbol.as_str())
+                        .copied()
+                        .unwrap_or_default()
+                })
+                .sum();
+            let income_per_hour = total / peers.len() as f64;
+            if income_per_hour <= 0.0 {
+                return None;
+            }
+            Some(Candidate {
+                offer: offer.clone(),
+                income_per_hour,
+                payback_hours: offer.price as f64 / income_per_hour,
+            })
+        })
+        .filter(|candidate| candidate.payback_hours <= config.max_payback_hours)
+        .collect();
+    candidates.sort_by(|a, b| a.payback_hours.total_cmp(&b.payback_hours));
+    candidates
+}
+
+/// What a plan is based on.
+struct Known {
+    ships: Vec<Ship>,
+    shipyards: Vec<Shipyard>,
+    /// What each ship earned within the lookback.
+    reports: Vec<ShipReport>,
+}
+
+/// Our ships, the shipyards we know of, and what each ship earned since `since`.
+fn known(store: &Store, since: i64) -> Result<Known, anyhow::Error> {
+    let ships = store.all::<Ship>()?.into_iter().map(|s| s.value).collect();
+    let shipyards = store
+        .all::<Shipyard>()?
+        .into_iter()
+        .map(|s| s.value)
+        .collect();
+    let report = store.report(&LedgerQuery {
+        since: Some(since),
+        ..Default::default()
+    })?;
+    Ok(Known {
+        ships,
+        shipyards,
+        reports: report.ships,
+    })
+}
+
+/// Buy the ship that pays for itself the quickest, now and then, as long as
+/// the credits stay above the reserve. In a dry run, only report what would be bought.
+///
+/// At most one ship is bought per run, so that its income is seen before the next one.
+#[instrument(name = "expansion", level = Level::INFO, skip_all)]
+pub async fn run(
+    config: ExpansionConfig,
+    client: Arc<Mutex<Client>>,
+    store: Arc<Store>,
+    shutdown: CancellationToken,
+) -> Result<(), anyhow::Error> {
+    let mut ticker = interval(Duration::from_secs(config.interval_secs.max(1)));
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => return Ok(()),
+            _ = ticker.tick() => {}
+        }
+
+        let now = Utc::now().timestamp_millis();
+        let since = now - config.lookback_hours as i64 * 60 * 60 * 1000;
+        let Known {
+            ships,
+            shipyards,
+            reports,
+        } = match known(&store, since) {
+            Ok(known) => known,
+            Err(err) => {
+                event!(Level::WARN, "Failed to read the store: {err:?}");
+                continue;
+            }
+        };
+        let candidates = plan(&config, &offers(&shipyards, &ships), &ships, &reports, now);
+
+        let credits = match client.lock().await.get_agent().await {
+            Ok(agent) => agent.credits,
+            Err(err) => {
+                event!(Level::WARN, "Failed to fetch agent: {err:?}");
+                continue;
+            }
+        };
+        let affordable = candidates
+            .iter()
+            .find(|c| credits - c.offer.price as i64 >= config.reserve as i64);
+        let Some(candidate) = affordable else {
+            event!(
+                Level::INFO,
+                "Nothing worth buying with {credits} credits: {candidates:?}"
+            );
+            continue;
+        };
+
+        let Offer {
+            ship_type,
+            shipyard,
+            price,
+        } = &candidate.offer;
+        if config.dry_run {
+            event!(
+                Level::INFO,
+                "Would buy a {ship_type:?} at {shipyard} for {price} credits, paying back in {:.1} hours",
+                candidate.payback_hours
+            );
+            continue;
+        }
+        let purchase = client::in_task("expansion", async {
+            client
+                .lock()
+                .await
+                .purchase_ship(*ship_type, shipyard.clone())
+                .await
+        });
+        match purchase.await {
+            Ok((_, ship, _)) => event!(
+                Level::INFO,
+                "Bought {} as a {ship_type:?} at {shipyard} for {price} credits",
+                ship.symbol
+            ),
+            Err(err) => event!(Level::WARN, "Failed to buy a {ship_type:?}: {err:?}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::fixtures;
+
+    const HOUR: i64 = 60 * 60 * 1000;
+
+    /// A ship that earned `net` since `hours` ago.
+    fn report(ship: &str, net: i64, hours: i64) -> ShipReport {
+        ShipReport {
+            ship_symbol: ship.to_string(),
+            first_recorded_at: 100 * HOUR - hours * HOUR,
+            income: net.max(0),
+            expenses: (-net).max(0),
+            net,
+            fuel: 0,
+            transactions: 1,
+        }
+    }
+
+    fn offer(ship_type: ShipType, price: u64) -> Offer {
+        Offer {
+            ship_type,
+            shipyard: "X1-A-1".to_string(),
+            price,
+        }
+    }
+
+    #[test]
+    fn buys_what_pays_back_the_quickest() {
+        let config = ExpansionConfig {
+            max_payback_hours: 10.0,
+            lookback_hours: 10,
+            ..Default::default()
+        };
+        let mut hauler = fixtures::ship("CAT-3", "X1-A-1");
+        hauler.registration.role = ShipRole::Hauler;
+        let ships = [
+            fixtures::ship("CAT-1", "X1-A-1"),
+            fixtures::ship("CAT-2", "X1-A-1"),
+            hauler,
+        ];
+        // Miners make 1000 an hour on average, the hauler 500. The second miner
+        // was only bought two hours ago.
+        let reports = [
+            report("CAT-1", 15_000, 10),
+            report("CAT-2", 1_000, 2),
+            report("CAT-3", 5_000, 10),
+        ];
+        let offers = [
+            offer(ShipType::ShipLightHauler, 2_000),
+            offer(ShipType::ShipMiningDrone, 3_000),
+            offer(ShipType::ShipProbe, 1_000),
+            offer(ShipType::ShipSurveyor, 1_000),
+        ];
+
+        let candidates = plan(&config, &offers, &ships, &reports, 100 * HOUR);
+
+        let types: Vec<_> = candidates.iter().map(|c| c.offer.ship_type).collect();
+        assert_eq!(
+            types,
+            [ShipType::ShipMiningDrone, ShipType::ShipLightHauler]
+        );
+        assert_eq!(candidates[0].payback_hours, 3.0);
+
+        let strict = ExpansionConfig {
+            max_payback_hours: 3.5,
+            ..config
+        };
+        assert_eq!(
+            plan(&strict, &offers, &ships, &reports, 100 * HOUR).len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,329 @@
+use std::time::Duration;
+
+use anyhow::bail;
+use serde::Deserialize;
+use tracing::{event, Level};
+
+use super::{context::worn, Behavior, BehaviorKind, ShipContext, Step, StepFuture};
+use crate::{
+    model::{Construction, Ship, TradeSymbol, WaypointType},
+    store::{LedgerQuery, PriceRecord},
+};
+
+/// How long to wait when the budget is used up or there are not enough credits.
+const BUDGET_WAIT: Duration = Duration::from_secs(600);
+
+/// How long to wait before looking at a complete site again.
+const COMPLETE_WAIT: Duration = Duration::from_secs(3600);
+
+/// Settings of the construction behavior.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct ConstructingConfig {
+    /// The construction site to supply. Defaults to the jump gate of our headquarters' system.
+    pub site: Option<String>,
+    /// Credits that are always left for everything else.
+    pub reserve: u64,
+    /// The most credits to spend on construction in total, if there is a limit.
+    /// Everything ships spent while constructing counts, fuel included.
+    pub budget: Option<u64>,
+}
+
+/// Buys the materials a construction site still needs and delivers them,
+/// while staying within the budget and never spending the reserve.
+///
+/// Materials are bought at the cheapest known market of the site's system.
+#[derive(Debug)]
+pub struct Constructing {
+    config: ConstructingConfig,
+    site: Option<String>,
+}
+
+impl Constructing {
+    pub fn new(config: ConstructingConfig) -> Self {
+        Self {
+            site: config.site.clone(),
+            config,
+        }
+    }
+
+    async fn next(&mut self, ctx: &mut ShipContext) -> Result<Step, anyhow::Error> {
+        let ship = ctx.ship().await?;
+        if worn(&ship) {
+            return ctx.repair(&ship).await;
+        }
+
+        let site = match &self.site {
+            Some(site) => site.clone(),
+            None => {
+                let site = home_gate(ctx).await?;
+                self.site = Some(site.clone());
+                site
+            }
+        };
+        let construction = ctx
+            .client()
+            .await
+            .get_construction_site(site.clone())
+            .await?;
+        if construction.is_complete {
+            event!(Level::INFO, "Construction of {site} is complete");
+            return Ok(Step::Wait(COMPLETE_WAIT));
+        }
+        let needed = remaining(&construction);
+
+        if ship
+            .cargo
+            .inventory
+            .iter()
+            .any(|item| needed.iter().any(|(good, _)| *good == item.symbol))
+        {
+            return supply(ctx, &ship, &construction, &needed).await;
+        }
+        if ship.cargo.units > 0 {
+            return ctx.sell_at_best_market(&ship).await;
+        }
+
+        self.buy(ctx, &ship, &site, &needed).await
+    }
+
+    /// Buy as much of a needed material as the hold, the credits and the budget allow,
+    /// picking the material of which the most units can be bought.
+    async fn buy(
+        &self,
+        ctx: &ShipContext,
+        ship: &Ship,
+        site: &str,
+        needed: &[(TradeSymbol, u64)],
+    ) -> Result<Step, anyhow::Error> {
+        let system = site.rsplit_once('-').map_or(site, |(system, _)| system);
+        let prices = ctx.store().latest_prices(system)?;
+        if !prices
+            .iter()
+            .any(|record| needed.iter().any(|(good, _)| *good == record.trade_symbol))
+        {
+            bail!("No known market in {system} sells any of the materials {site} needs");
+        }
+
+        let credits = ctx.client().await.get_agent().await?.credits;
+        let spent = ctx
+            .store()
+            .report(&LedgerQuery {
+                task: Some(BehaviorKind::Constructing.task_name().to_string()),
+                ..Default::default()
+            })?
+            .expenses;
+        let floor = floor(credits, self.config.reserve, self.config.budget, spent);
+        let spendable = (credits - floor as i64).max(0) as u64;
+        let space = ship.cargo.capacity - ship.cargo.units;
+        let Some((record, units)) = choose(needed, &prices, space, spendable) else {
+            event!(
+                Level::INFO,
+                "Waiting for credits to buy materials for {site}"
+            );
+            return Ok(Step::Wait(BUDGET_WAIT));
+        };
+
+        if ship.nav.waypoint_symbol != record.waypoint_symbol {
+            ctx.navigate(ship, &record.waypoint_symbol).await?;
+            return Ok(Step::Continue);
+        }
+        ctx.dock(ship).await?;
+        let market = ctx.market(ship).await?;
+        ctx.refuel(ship, &market).await?;
+        // Prices move while buying, so the purchase stops at the credits to keep instead.
+        let bought = ctx
+            .purchase(&market, record.trade_symbol, units, u64::MAX, floor)
+            .await?;
+        event!(
+            Level::INFO,
+            "Bought {bought} units of {:?} for {site}",
+            record.trade_symbol
+        );
+
+        Ok(Step::Continue)
+    }
+}
+
+impl Behavior for Constructing {
+    fn step<'a>(&'a mut self, ship: &'a mut ShipContext) -> StepFuture<'a> {
+        Box::pin(self.next(ship))
+    }
+}
+
+/// The jump gate of the system our headquarters are in.
+async fn home_gate(ctx: &ShipContext) -> Result<String, anyhow::Error> {
+    let headquarters = ctx.client().await.get_agent().await?.headquarters;
+    let system = headquarters
+        .rsplit_once('-')
+        .map_or(headquarters.as_str(), |(system, _)| system);
+    let gate = ctx
+        .store()
+        .waypoints_in_system(system)?
+        .into_iter()
+        .find(|w| w.value.waypoint_type == WaypointType::JumpGate);
+    match gate {
+        Some(gate) => Ok(gate.value.symbol),
+        None => bail!("No known jump gate in {system}"),
+    }
+}
+
+/// The units of each material the site still needs.
+fn remaining(construction: &Construction) -> Vec<(TradeSymbol, u64)> {
+    construction
+        .materials
+        .iter()
+        .map(|m| (m.trade_symbol, m.required.saturating_sub(m.fulfilled)))
+        .filter(|(_, units)| *units > 0)
+        .collect()
+}
+
+/// The cheapest market of each needed material, along with how many units of it fit into
+/// `space` and can be bought with `spendable` credits there. The material with the most
+/// such units wins, so that one that is too expensive does not hold up the others.
+fn choose<'a>(
+    needed: &[(TradeSymbol, u64)],
+    prices: &'a [PriceRecord],
+    space: u64,
+    spendable: u64,
+) -> Option<(&'a PriceRecord, u64)> {
+    needed
+        .iter()
+        .filter_map(|(good, units)| {
+            let record = prices
+                .iter()
+                .filter(|record| record.trade_symbol == *good)
+                .min_by_key(|record| record.purchase_price)?;
+            let units = (*units)
+                .min(space)
+                .min(spendable / record.purchase_price.max(1));
+            Some((record, units))
+        })
+        .filter(|(_, units)| *units > 0)
+        .max_by_key(|(record, units)| (*units, std::cmp::Reverse(record.purchase_price)))
+}
+
+/// The credits to keep, so that neither the reserve is touched nor the budget exceeded.
+fn floor(credits: i64, reserve: u64, budget: Option<u64>, spent: i64) -> u64 {
+    let Some(budget) = budget else {
+        return reserve;
+    };
+    let left = budget.saturating_sub(spent.max(0) as u64);
+    reserve.max((credits.max(0) as u64).saturating_sub(left))
+}
+
+/// Deliver the needed materials in the cargo hold to the site.
+async fn supply(
+    ctx: &ShipContext,
+    ship: &Ship,
+    construction: &Construction,
+    needed: &[(TradeSymbol, u64)],
+) -> Result<Step, anyhow::Error> {
+    let site = &construction.symbol;
+    if &ship.nav.waypoint_symbol != site {
+        ctx.navigate(ship, site).await?;
+        return Ok(Step::Continue);
+    }
+    ctx.dock(ship).await?;
+
+    let mut construction = construction.clone();
+    for item in &ship.cargo.inventory {
+        let Some((_, units)) = needed.iter().find(|(good, _)| *good == item.symbol) else {
+            continue;
+        };
+        let units = item.units.min(*units);
+        (_, construction) = ctx
+            .client()
+            .await
+            .supply_construction(site.clone(), ctx.symbol.clone(), item.symbol, units)
+            .await?;
+        event!(
+            Level::INFO,
+            "Supplied {units} units of {:?} to {site}",
+            item.symbol
+        );
+    }
+
+    for material in &construction.materials {
+        event!(
+            Level::INFO,
+            "{site} has {}/{} units of {:?}",
+            material.fulfilled,
+            material.required,
+            material.trade_symbol
+        );
+    }
+
+    Ok(Step::Continue)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{SupplyLevel, TradeGoodType};
+
+    fn price(waypoint: &str, good: TradeSymbol, purchase: u64) -> PriceRecord {
+        PriceRecord {
+            waypoint_symbol: waypoint.to_string(),
+            trade_symbol: good,
+            recorded_at: 0,
+            good_type: TradeGoodType::Export,
+            supply: SupplyLevel::Moderate,
+            activity: None,
+            purchase_price: purchase,
+            sell_price: purchase / 2,
+            trade_volume: 10,
+        }
+    }
+
+    #[test]
+    fn spends_neither_the_reserve_nor_beyond_the_budget() {
+        assert_eq!(floor(10_000, 0, None, 0), 0);
+        assert_eq!(floor(10_000, 4_000, None, 0), 4_000);
+        assert_eq!(floor(10_000, 4_000, Some(5_000), 3_000), 8_000);
+        assert_eq!(floor(10_000, 0, Some(5_000), 6_000), 10_000);
+        assert_eq!(floor(1_000, 4_000, None, 0), 4_000);
+    }
+
+    #[test]
+    fn needs_what_is_not_fulfilled_yet() {
+        let construction: Construction = serde_json::from_value(serde_json::json!({
+            "symbol": "X1-A-1",
+            "materials": [
+                { "tradeSymbol": "FAB_MATS", "required": 1600, "fulfilled": 1600 },
+                { "tradeSymbol": "ADVANCED_CIRCUITRY", "required": 400, "fulfilled": 150 }
+            ],
+            "isComplete": false
+        }))
+        .unwrap();
+
+        assert_eq!(
+            remaining(&construction),
+            [(TradeSymbol::AdvancedCircuitry, 250)]
+        );
+    }
+
+    #[test]
+    fn buys_another_material_when_the_first_is_too_expensive() {
+        let needed = [
+            (TradeSymbol::AdvancedCircuitry, 400),
+            (TradeSymbol::FabMats, 1600),
+        ];
+        let prices = [
+            price("X1-A-2", TradeSymbol::AdvancedCircuitry, 5_000),
+            price("X1-A-3", TradeSymbol::FabMats, 400),
+            price("X1-A-4", TradeSymbol::FabMats, 300),
+        ];
+
+        let (record, units) = choose(&needed, &prices, 40, 3_000).unwrap();
+        assert_eq!(record.waypoint_symbol, "X1-A-4");
+        assert_eq!(units, 10);
+
+        let (record, units) = choose(&needed, &prices, 40, 100_000).unwrap();
+        assert_eq!(record.trade_symbol, TradeSymbol::FabMats);
+        assert_eq!(units, 40);
+
+        assert!(choose(&needed, &prices, 40, 200).is_none());
+        assert!(choose(&needed, &prices, 0, 100_000).is_none());
+    }
+}
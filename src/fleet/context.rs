@@ -0,0 +1,559 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::bail;
+use chrono::{DateTime, Utc};
+use tokio::sync::{Mutex, MutexGuard};
+use tracing::{event, Level};
+
+use super::{haul::Haulers, scout::Scouts, Step};
+use crate::{
+    cache::Cache,
+    client::Client,
+    model::{
+        Market, Ship, ShipConditionEvent, ShipNavStatus, TradeGoodAmount, TradeSymbol,
+        WaypointTraitSymbol,
+    },
+    store::Store,
+};
+
+/// Ships with a component in a worse condition than this get repaired.
+const MIN_CONDITION: f64 = 0.3;
+
+/// A ship as seen by its behavior, along with the means to command it.
+///
+/// The ship itself is kept up to date by the cache, which observes every response.
+#[derive(Debug)]
+pub struct ShipContext {
+    pub symbol: String,
+    client: Arc<Mutex<Client>>,
+    cache: Arc<Cache>,
+    store: Arc<Store>,
+    haulers: Arc<Haulers>,
+    scouts: Arc<Scouts>,
+}
+
+impl ShipContext {
+    pub fn new(
+        symbol: String,
+        client: Arc<Mutex<Client>>,
+        cache: Arc<Cache>,
+        store: Arc<Store>,
+        haulers: Arc<Haulers>,
+        scouts: Arc<Scouts>,
+    ) -> Self {
+        Self {
+            symbol,
+            client,
+            cache,
+            store,
+            haulers,
+            scouts,
+        }
+    }
+
+    /// The client, for requests that don't have a helper here.
+    /// Hold on to it only for as long as necessary, every other ship shares it.
+    pub async fn client(&self) -> MutexGuard<'_, Client> {
+        self.client.lock().await
+    }
+
+    pub fn store(&self) -> &Store {
+        &self.store
+    }
+
+    /// The haulers parked around the universe, shared by all ships.
+    pub fn haulers(&self) -> &Haulers {
+        &self.haulers
+    }
+
+    /// The markets and shipyards claimed by probes, shared by all ships.
+    pub fn scouts(&self) -> &Scouts {
+        &self.scouts
+    }
+
+    /// The latest known state of the ship, fetched if it is not known yet.
+    pub async fn ship(&self) -> Result<Ship, anyhow::Error> {
+        if let Some(ship) = self.cache.ship(&self.symbol, Duration::MAX) {
+            return Ok(ship);
+        }
+        Ok(*self.client().await.get_ship(self.symbol.clone()).await?)
+    }
+
+    /// How long until the ship arrives, if it is in transit.
+    pub async fn arrival(&self) -> Result<Option<Duration>, anyhow::Error> {
+        let nav = self.ship().await?.nav;
+        if nav.status != ShipNavStatus::InTransit {
+            return Ok(None);
+        }
+
+        // Past arrival times still need the nav to be fetched again.
+        Ok(Some(until(&nav.route.arrival)?.unwrap_or_default()))
+    }
+
+    /// Fetch the nav after arriving, because the known one still says the ship is in transit.
+    pub async fn arrived(&self) -> Result<(), anyhow::Error> {
+        self.client()
+            .await
+            .get_ship_nav(self.symbol.clone())
+            .await?;
+        Ok(())
+    }
+
+    pub async fn orbit(&self, ship: &Ship) -> Result<(), anyhow::Error> {
+        if ship.nav.status == ShipNavStatus::Docked {
+            self.client().await.orbit_ship(self.symbol.clone()).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn dock(&self, ship: &Ship) -> Result<(), anyhow::Error> {
+        if ship.nav.status == ShipNavStatus::InOrbit {
+            self.client().await.dock_ship(self.symbol.clone()).await?;
+        }
+        Ok(())
+    }
+
+    /// Set off for `destination`. The runtime waits for the ship to arrive before the next step.
+    pub async fn navigate(&self, ship: &Ship, destination: &str) -> Result<(), anyhow::Error> {
+        self.orbit(ship).await?;
+        let (_, _, events) = self
+            .client()
+            .await
+            .navigate_ship(self.symbol.clone(), destination.to_string())
+            .await?;
+        self.conditions(&events);
+        Ok(())
+    }
+
+    /// Fetch the market the docked ship is at, which records its prices as well.
+    pub async fn market(&self, ship: &Ship) -> Result<Market, anyhow::Error> {
+        self.client()
+            .await
+            .get_market(ship.nav.waypoint_symbol.clone())
+            .await
+    }
+
+    /// Sell everything in the cargo hold that `market` buys, in batches
+    /// no larger than its trade volume. Returns the credits earned.
+    pub async fn sell_cargo(&self, ship: &Ship, market: &Market) -> Result<u64, anyhow::Error> {
+        let mut earned = 0;
+        for item in &ship.cargo.inventory {
+            let Some(good) = market
+                .trade_goods
+                .iter()
+                .flatten()
+                .find(|good| good.symbol == item.symbol)
+            else {
+                continue;
+            };
+
+            let mut units = item.units;
+            while units > 0 {
+                let batch = units.min(good.trade_volume.max(1));
+                let (_, _, transaction) = self
+                    .client()
+                    .await
+                    .sell_cargo(
+                        self.symbol.clone(),
+                        TradeGoodAmount {
+                            trade_symbol: item.symbol,
+                            units: batch,
+                        },
+                    )
+                    .await?;
+                earned += transaction.total_price;
+                units -= batch;
+            }
+        }
+        Ok(earned)
+    }
+
+    /// Buy up to `units` of `good` at the market the ship is docked at, in batches no larger
+    /// than its trade volume, for as long as the price stays below `max_price` and the credits
+    /// stay at or above `floor`, going by the last price paid. Returns the units bought.
+    pub async fn purchase(
+        &self,
+        market: &Market,
+        good: TradeSymbol,
+        units: u64,
+        max_price: u64,
+        floor: u64,
+    ) -> Result<u64, anyhow::Error> {
+        let Some(listing) = market
+            .trade_goods
+            .iter()
+            .flatten()
+            .find(|listing| listing.symbol == good)
+        else {
+            return Ok(0);
+        };
+
+        let mut credits = self.client().await.get_agent().await?.credits;
+        let mut price = listing.purchase_price;
+        let mut bought = 0;
+        while bought < units && price < max_price {
+            let spendable = (credits - floor as i64).max(0) as u64;
+            let batch = (units - bought)
+                .min(listing.trade_volume.max(1))
+                .min(spendable / price.max(1));
+            if batch == 0 {
+                break;
+            }
+            let (agent, _, transaction) = self
+                .client()
+                .await
+                .purchase_cargo(
+                    self.symbol.clone(),
+                    TradeGoodAmount {
+                        trade_symbol: good,
+                        units: batch,
+                    },
+                )
+                .await?;
+            bought += batch;
+            credits = agent.credits;
+            price = transaction.price_per_unit;
+        }
+        Ok(bought)
+    }
+
+    /// Fill up the tanks if they are not full and `market` sells fuel.
+    pub async fn refuel(&self, ship: &Ship, market: &Market) -> Result<(), anyhow::Error> {
+        if sells_fuel(market) && ship.fuel.current < ship.fuel.capacity {
+            self.client()
+                .await
+                .refuel_ship(self.symbol.clone(), None, None)
+                .await?;
+        }
+        Ok(())
+    }
+
+    pub async fn jettison(&self, good: TradeSymbol, units: u64) -> Result<(), anyhow::Error> {
+        event!(Level::DEBUG, "Jettisoning {units} units of {good:?}");
+        self.client()
+            .await
+            .jettison_cargo(
+                self.symbol.clone(),
+                TradeGoodAmount {
+                    trade_symbol: good,
+                    units,
+                },
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Hand cargo over to another ship at the same waypoint.
+    pub async fn transfer(
+        &self,
+        good: TradeSymbol,
+        units: u64,
+        target: &str,
+    ) -> Result<(), anyhow::Error> {
+        let mut client = self.client().await;
+        client
+            .transfer_cargo(
+                self.symbol.clone(),
+                TradeGoodAmount {
+                    trade_symbol: good,
+                    units,
+                },
+                target.to_string(),
+            )
+            .await?;
+        // Observers only learn about the cargo of the ship the request was about.
+        client.get_ship_cargo(target.to_string()).await?;
+        Ok(())
+    }
+
+    /// The latest known state of another one of our ships.
+    pub fn other_ship(&self, symbol: &str) -> Option<Ship> {
+        self.cache.ship(symbol, Duration::MAX)
+    }
+
+    /// Sell the cargo at the markets that pay the most for it, one after the other,
+    /// and refuel at each. Goods no known market of the system buys are jettisoned,
+    /// because they would otherwise fill up the hold for good.
+    pub async fn sell_at_best_market(&self, ship: &Ship) -> Result<Step, anyhow::Error> {
+        self.sell_except(ship, &[]).await
+    }
+
+    /// Like [`ShipContext::sell_at_best_market`], but leaves the goods in `keep` alone.
+    pub async fn sell_except(
+        &self,
+        ship: &Ship,
+        keep: &[TradeSymbol],
+    ) -> Result<Step, anyhow::Error> {
+        let selling = without(ship, keep);
+        let traded = traded(&self.store, &ship.nav.system_symbol)?;
+        if let Some(item) = selling
+            .cargo
+            .inventory
+            .iter()
+            .find(|item| !traded.contains(&item.symbol))
+        {
+            self.jettison(item.symbol, item.units).await?;
+            return Ok(Step::Continue);
+        }
+
+        let Some(destination) = best_market(&self.store, &selling)? else {
+            return Ok(Step::Continue);
+        };
+        if ship.nav.waypoint_symbol != destination {
+            self.navigate(ship, &destination).await?;
+            return Ok(Step::Continue);
+        }
+        self.dock(ship).await?;
+
+        let market = self.market(ship).await?;
+        let earned = self.sell_cargo(&selling, &market).await?;
+        event!(
+            Level::INFO,
+            "Sold cargo at {destination} for {earned} credits"
+        );
+        self.refuel(ship, &market).await?;
+
+        let left = without(&self.ship().await?, keep);
+        match best_market(&self.store, &left)? {
+            Some(next) if next != destination => self.navigate(&left, &next).await?,
+            // The market was expected to buy the rest, but did not.
+            _ => {
+                for item in &left.cargo.inventory {
+                    self.jettison(item.symbol, item.units).await?;
+                }
+            }
+        }
+
+        Ok(Step::Continue)
+    }
+
+    /// Go to the closest known shipyard of the system and repair the ship there.
+    pub async fn repair(&self, ship: &Ship) -> Result<Step, anyhow::Error> {
+        let waypoints = self.store.waypoints_in_system(&ship.nav.system_symbol)?;
+        let here = waypoints
+            .iter()
+            .find(|w| w.value.symbol == ship.nav.waypoint_symbol)
+            .map_or((0, 0), |w| (w.value.x, w.value.y));
+        let Some(shipyard) = waypoints
+            .iter()
+            .map(|w| &w.value)
+            .filter(|w| {
+                w.traits
+                    .iter()
+                    .any(|t| t.symbol == WaypointTraitSymbol::Shipyard)
+            })
+            .min_by_key(|w| (w.x - here.0).pow(2) + (w.y - here.1).pow(2))
+        else {
+            bail!(
+                "No known shipyard in {} to repair at",
+                ship.nav.system_symbol
+            );
+        };
+
+        if ship.nav.waypoint_symbol != shipyard.symbol {
+            self.navigate(ship, &shipyard.symbol).await?;
+            return Ok(Step::Continue);
+        }
+        self.dock(ship).await?;
+        let (_, _, transaction) = self.client().await.repair_ship(self.symbol.clone()).await?;
+        event!(
+            Level::INFO,
+            "Repaired at {} for {} credits",
+            shipyard.symbol,
+            transaction.total_price
+        );
+        Ok(Step::Continue)
+    }
+
+    /// Log what happened to the ship along the way.
+    /// The condition of the ship itself is kept up to date by the cache.
+    pub fn conditions(&self, events: &[ShipConditionEvent]) {
+        for condition in events {
+            event!(
+                Level::WARN,
+                "{:?} damaged the {:?}: {}",
+                condition.symbol,
+                condition.component,
+                condition.name
+            );
+        }
+    }
+}
+
+/// The market in the ship's system that pays the most for its cargo, going by the latest
+/// known prices. If no prices are known, the first market that trades any of it at all.
+pub fn best_market(store: &Store, ship: &Ship) -> Result<Option<String>, anyhow::Error> {
+    let prices = store.latest_prices(&ship.nav.system_symbol)?;
+    let units = |good: TradeSymbol| -> u64 {
+        ship.cargo
+            .inventory
+            .iter()
+            .filter(|item| item.symbol == good)
+            .map(|item| item.units)
+            .sum()
+    };
+
+    let mut values: BTreeMap<&str, u64> = BTreeMap::new();
+    for record in &prices {
+        *values.entry(&record.waypoint_symbol).or_default() +=
+            units(record.trade_symbol) * record.sell_price;
+    }
+    let best = values
+        .into_iter()
+        .filter(|(_, value)| *value > 0)
+        .max_by_key(|(_, value)| *value);
+    if let Some((symbol, _)) = best {
+        return Ok(Some(symbol.to_string()));
+    }
+
+    let prefix = format!("{}-", ship.nav.system_symbol);
+    let market = store.all::<Market>()?.into_iter().find(|market| {
+        let market = &market.value;
+        market.symbol.starts_with(&prefix)
+            && [&market.imports, &market.exports, &market.exchange]
+                .into_iter()
+                .flatten()
+                .any(|good| units(good.symbol) > 0)
+    });
+    Ok(market.map(|market| market.value.symbol))
+}
+
+/// The ship as if the goods in `keep` were not in its hold.
+fn without(ship: &Ship, keep: &[TradeSymbol]) -> Ship {
+    let mut ship = ship.clone();
+    ship.cargo
+        .inventory
+        .retain(|item| !keep.contains(&item.symbol));
+    ship
+}
+
+/// The goods any known market of a system trades.
+fn traded(store: &Store, system: &str) -> Result<HashSet<TradeSymbol>, anyhow::Error> {
+    let mut goods: HashSet<_> = store
+        .latest_prices(system)?
+        .into_iter()
+        .map(|record| record.trade_symbol)
+        .collect();
+    let prefix = format!("{system}-");
+    for market in store.all::<Market>()? {
+        let market = market.value;
+        if market.symbol.starts_with(&prefix) {
+            goods.extend(
+                [&market.imports, &market.exports, &market.exchange]
+                    .into_iter()
+                    .flatten()
+                    .map(|good| good.symbol),
+            );
+        }
+    }
+    Ok(goods)
+}
+
+/// Whether the market sells fuel. Based on the goods it lists rather than its trade goods,
+/// so that the answer is the same whether or not a ship is there to see the prices.
+pub fn sells_fuel(market: &Market) -> bool {
+    market
+        .exports
+        .iter()
+        .chain(&market.imports)
+        .chain(&market.exchange)
+        .any(|good| good.symbol == TradeSymbol::Fuel)
+}
+
+/// Whether a component of the ship is in such a bad condition that it should be repaired.
+pub fn worn(ship: &Ship) -> bool {
+    [
+        &ship.frame.condition,
+        &ship.reactor.condition,
+        &ship.engine.condition,
+    ]
+    .into_iter()
+    .any(|condition| condition.0 < MIN_CONDITION)
+}
+
+/// How long until the ship's cooldown expires, if it is still cooling down.
+pub fn cooldown(ship: &Ship) -> Result<Option<Duration>, anyhow::Error> {
+    match &ship.cooldown.expiration {
+        Some(expiration) => until(expiration),
+        None => Ok(None),
+    }
+}
+
+/// How long until `time`, or `None` if it already passed.
+fn until(time: &str) -> Result<Option<Duration>, anyhow::Error> {
+    let time = DateTime::parse_from_rfc3339(time)?.with_timezone(&Utc);
+    Ok((time - Utc::now()).to_std().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::fixtures;
+
+    #[test]
+    fn sells_cargo_split_across_two_markets_at_both() {
+        let store = Store::open_in_memory().unwrap();
+        for (waypoint, good) in [("X1-A-2", "IRON_ORE"), ("X1-A-3", "COPPER_ORE")] {
+            let market = serde_json::from_value(serde_json::json!({
+                "symbol": waypoint,
+                "exports": [],
+                "imports": [],
+                "exchange": [],
+                "tradeGoods": [
+                    { "symbol": good, "type": "IMPORT", "tradeVolume": 10,
+                      "supply": "SCARCE", "purchasePrice": 80, "sellPrice": 40 }
+                ]
+            }))
+            .unwrap();
+            store.record_prices(&market).unwrap();
+        }
+        let mut ship = fixtures::ship("CAT-1", "X1-A-1");
+        ship.cargo = serde_json::from_value(serde_json::json!({
+            "capacity": 30,
+            "units": 30,
+            "inventory": [
+                { "symbol": "IRON_ORE", "name": "", "description": "", "units": 10 },
+                { "symbol": "COPPER_ORE", "name": "", "description": "", "units": 15 },
+                { "symbol": "QUARTZ_SAND", "name": "", "description": "", "units": 5 }
+            ]
+        }))
+        .unwrap();
+
+        let traded = traded(&store, "X1-A").unwrap();
+        assert!(traded.contains(&TradeSymbol::IronOre));
+        assert!(traded.contains(&TradeSymbol::CopperOre));
+        assert!(!traded.contains(&TradeSymbol::QuartzSand));
+
+        assert_eq!(
+            best_market(&store, &ship).unwrap().as_deref(),
+            Some("X1-A-3")
+        );
+        let left = without(&ship, &[TradeSymbol::CopperOre]);
+        assert_eq!(
+            best_market(&store, &left).unwrap().as_deref(),
+            Some("X1-A-2")
+        );
+    }
+
+    #[test]
+    fn sells_fuel_without_a_ship_to_see_the_prices() {
+        let market: Market = serde_json::from_value(serde_json::json!({
+            "symbol": "X1-A-2",
+            "exports": [],
+            "imports": [],
+            "exchange": [{ "symbol": "FUEL", "name": "Fuel", "description": "" }]
+        }))
+        .unwrap();
+        assert!(sells_fuel(&market));
+
+        let market = Market {
+            exchange: vec![],
+            ..market
+        };
+        assert!(!sells_fuel(&market));
+    }
+}
@@ -0,0 +1,333 @@
+use std::time::Duration;
+
+use anyhow::bail;
+use chrono::Utc;
+use serde::Deserialize;
+use tracing::{event, Level};
+
+use super::{context::worn, mining::Mining, Behavior, MiningConfig, ShipContext, Step, StepFuture};
+use crate::{
+    contracts::{self, ContractStatus, Deadline, Delivery},
+    model::{Contract, ContractType, Ship},
+};
+
+/// How long to wait before looking again when no offer is worth accepting.
+const NO_OFFER_WAIT: Duration = Duration::from_secs(3600);
+
+/// Settings of the contract behavior.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct ContractingConfig {
+    /// Offers are only accepted if they are expected to earn at least this much.
+    /// Offers whose cost is unknown are accepted if an asteroid is configured to mine the goods.
+    pub min_profit: i64,
+    /// Where to mine goods that no known market sells.
+    pub asteroid: Option<String>,
+}
+
+/// What to do about our contracts.
+#[derive(Debug)]
+enum Plan<'a> {
+    /// Deliver the goods of an accepted contract, or fulfill it.
+    Work(&'a ContractStatus),
+    Accept(&'a ContractStatus),
+    /// There is nothing to work on and no offer either.
+    Negotiate,
+    /// There are offers, but none is worth it.
+    Wait,
+}
+
+/// Work on the accepted contract, or else accept the most profitable offer.
+/// Only procurement contracts to be delivered in `system` are considered,
+/// and none whose deadline passed.
+fn plan<'a>(statuses: &'a [ContractStatus], system: &str, config: &ContractingConfig) -> Plan<'a> {
+    let open: Vec<_> = statuses
+        .iter()
+        .filter(|status| status.seconds_left.is_some_and(|left| left > 0))
+        .filter(|status| status.contract.contract_type == ContractType::Procurement)
+        .collect();
+
+    if let Some(status) = open.iter().find(|s| s.deadline == Some(Deadline::Fulfill)) {
+        return Plan::Work(status);
+    }
+
+    let offers: Vec<_> = open
+        .into_iter()
+        .filter(|status| status.deadline == Some(Deadline::Accept))
+        .collect();
+    if offers.is_empty() {
+        return Plan::Negotiate;
+    }
+    offers
+        .into_iter()
+        .filter(|status| {
+            status
+                .deliveries
+                .iter()
+                .all(|d| d.destination_symbol.starts_with(&format!("{system}-")))
+        })
+        .filter(|status| match status.estimated_profit {
+            Some(profit) => profit >= config.min_profit,
+            None => config.asteroid.is_some(),
+        })
+        .max_by_key(|status| status.estimated_profit)
+        .map_or(Plan::Wait, Plan::Accept)
+}
+
+/// Whether an accepted contract passed its deadline without being fulfilled.
+fn expired(status: &ContractStatus) -> bool {
+    status.deadline == Some(Deadline::Fulfill) && status.seconds_left.is_some_and(|left| left <= 0)
+}
+
+/// Negotiates procurement contracts, accepts those worth it, buys or mines the goods,
+/// delivers them and has the contract fulfilled, one contract at a time.
+///
+/// Goods are bought at the cheapest known market of the system. Those that no market
+/// sells are mined at the configured asteroid, if there is one.
+#[derive(Debug)]
+pub struct Contracting {
+    config: ContractingConfig,
+}
+
+impl Contracting {
+    pub fn new(config: ContractingConfig) -> Self {
+        Self { config }
+    }
+
+    async fn next(&mut self, ctx: &mut ShipContext) -> Result<Step, anyhow::Error> {
+        let ship = ctx.ship().await?;
+        if worn(&ship) {
+            return ctx.repair(&ship).await;
+        }
+
+        let statuses = contracts::statuses(ctx.store(), Utc::now())?;
+        // Accepted contracts that ran out of time can't be worked on anymore.
+        // Forget them, so they don't stand in the way of new ones.
+        for status in statuses.iter().filter(|status| expired(status)) {
+            event!(
+                Level::WARN,
+                "Contract {} expired before it was fulfilled",
+                status.contract.id
+            );
+            ctx.store().remove::<Contract>(&status.contract.id)?;
+        }
+
+        match plan(&statuses, &ship.nav.system_symbol, &self.config) {
+            Plan::Work(status) => self.work(ctx, &ship, status).await,
+            Plan::Accept(status) => {
+                let id = status.contract.id.clone();
+                ctx.client().await.accept_contract(id.clone()).await?;
+                event!(
+                    Level::INFO,
+                    "Accepted contract {id}, expecting a profit of {:?} credits",
+                    status.estimated_profit
+                );
+                Ok(Step::Continue)
+            }
+            Plan::Negotiate => {
+                ctx.dock(&ship).await?;
+                let contract = ctx
+                    .client()
+                    .await
+                    .negotiate_contract(ctx.symbol.clone())
+                    .await?;
+                event!(Level::INFO, "Negotiated contract {}", contract.id);
+                Ok(Step::Continue)
+            }
+            Plan::Wait => {
+                event!(Level::INFO, "No contract offer is worth accepting");
+                Ok(Step::Wait(NO_OFFER_WAIT))
+            }
+        }
+    }
+
+    async fn work(
+        &self,
+        ctx: &ShipContext,
+        ship: &Ship,
+        status: &ContractStatus,
+    ) -> Result<Step, anyhow::Error> {
+        let id = &status.contract.id;
+        let Some(delivery) = status.deliveries.iter().find(|d| d.units_remaining > 0) else {
+            ctx.client().await.fulfill_contract(id.clone()).await?;
+            event!(
+                Level::INFO,
+                "Fulfilled contract {id} for {} credits",
+                status.payment_remaining
+            );
+            return Ok(Step::Continue);
+        };
+        let good = delivery.trade_symbol;
+
+        // Make room for the goods of the contract first. Miners throw out
+        // whatever else they extracted, other ships sell it.
+        if let Some(item) = ship.cargo.inventory.iter().find(|item| item.symbol != good) {
+            if self.config.asteroid.is_some() {
+                ctx.jettison(item.symbol, item.units).await?;
+                return Ok(Step::Continue);
+            }
+            return ctx.sell_except(ship, &[good]).await;
+        }
+
+        let carrying = ship.cargo.units;
+        let load = delivery.units_remaining.min(ship.cargo.capacity);
+        if carrying >= load {
+            return deliver(ctx, ship, id, delivery, carrying).await;
+        }
+
+        let prices = ctx.store().latest_prices(&ship.nav.system_symbol)?;
+        let cheapest = prices
+            .iter()
+            .filter(|record| record.trade_symbol == good)
+            .min_by_key(|record| record.purchase_price);
+        if let Some(record) = cheapest {
+            if ship.nav.waypoint_symbol != record.waypoint_symbol {
+                ctx.navigate(ship, &record.waypoint_symbol).await?;
+                return Ok(Step::Continue);
+            }
+            ctx.dock(ship).await?;
+            let market = ctx.market(ship).await?;
+            ctx.refuel(ship, &market).await?;
+            // Paying more per unit than the contract pays would lose credits.
+            let max_price = status.payment_remaining / status.units_remaining.max(1);
+            let bought = ctx
+                .purchase(&market, good, load - carrying, max_price, 0)
+                .await?;
+            if bought == 0 && carrying > 0 {
+                // The credits ran out or the price went up, so deliver what there is.
+                return deliver(ctx, ship, id, delivery, carrying).await;
+            }
+            if bought == 0 {
+                event!(
+                    Level::INFO,
+                    "{good:?} costs more at {} than contract {id} pays",
+                    record.waypoint_symbol
+                );
+                return Ok(Step::Wait(NO_OFFER_WAIT));
+            }
+            return Ok(Step::Continue);
+        }
+
+        match &self.config.asteroid {
+            Some(asteroid) => {
+                let mining = Mining::new(MiningConfig {
+                    asteroid: asteroid.clone(),
+                    keep: vec![good],
+                    jettison: Vec::new(),
+                });
+                mining.mine(ctx, ship).await
+            }
+            None => bail!(
+                "No known market in {} sells {good:?} and there is no asteroid to mine",
+                ship.nav.system_symbol
+            ),
+        }
+    }
+}
+
+async fn deliver(
+    ctx: &ShipContext,
+    ship: &Ship,
+    id: &str,
+    delivery: &Delivery,
+    carrying: u64,
+) -> Result<Step, anyhow::Error> {
+    if ship.nav.waypoint_symbol != delivery.destination_symbol {
+        ctx.navigate(ship, &delivery.destination_symbol).await?;
+        return Ok(Step::Continue);
+    }
+    ctx.dock(ship).await?;
+
+    let units = carrying.min(delivery.units_remaining);
+    ctx.client()
+        .await
+        .deliver_contract(
+            id.to_string(),
+            ctx.symbol.clone(),
+            delivery.trade_symbol,
+            units,
+        )
+        .await?;
+    event!(
+        Level::INFO,
+        "Delivered {units} units of {:?} for contract {id}",
+        delivery.trade_symbol
+    );
+
+    Ok(Step::Continue)
+}
+
+impl Behavior for Contracting {
+    fn step<'a>(&'a mut self, ship: &'a mut ShipContext) -> StepFuture<'a> {
+        Box::pin(self.next(ship))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{model::fixtures, store::Store};
+
+    fn statuses(contracts: &[(&str, bool, &str)]) -> Vec<ContractStatus> {
+        let store = Store::open_in_memory().unwrap();
+        for (id, accepted, destination) in contracts {
+            let mut contract = fixtures::contract(id, destination, 10, "2099-01-01T00:00:00Z");
+            contract.accepted = *accepted;
+            store.put(&contract).unwrap();
+        }
+        contracts::statuses(&store, Utc::now()).unwrap()
+    }
+
+    #[test]
+    fn works_on_the_accepted_contract_first() {
+        let config = ContractingConfig::default();
+
+        let both = statuses(&[("offer", false, "X1-A-1"), ("accepted", true, "X1-A-1")]);
+        assert!(matches!(
+            plan(&both, "X1-A", &config),
+            Plan::Work(status) if status.contract.id == "accepted"
+        ));
+
+        assert!(matches!(plan(&[], "X1-A", &config), Plan::Negotiate));
+    }
+
+    #[test]
+    fn looks_past_an_expired_contract_to_new_offers() {
+        let store = Store::open_in_memory().unwrap();
+        store
+            .put(&fixtures::contract(
+                "late",
+                "X1-A-1",
+                10,
+                "2000-01-01T00:00:00Z",
+            ))
+            .unwrap();
+        let mut offer = fixtures::contract("fresh", "X1-A-1", 10, "2099-01-01T00:00:00Z");
+        offer.accepted = false;
+        store.put(&offer).unwrap();
+        let statuses = contracts::statuses(&store, Utc::now()).unwrap();
+        let config = ContractingConfig {
+            asteroid: Some("X1-A-2".to_string()),
+            ..Default::default()
+        };
+
+        assert!(statuses.iter().any(expired));
+        assert!(matches!(
+            plan(&statuses, "X1-A", &config),
+            Plan::Accept(status) if status.contract.id == "fresh"
+        ));
+    }
+
+    #[test]
+    fn accepts_offers_only_when_they_can_be_sourced() {
+        let offers = statuses(&[("elsewhere", false, "X1-B-1"), ("here", false, "X1-A-1")]);
+        let mut config = ContractingConfig::default();
+        assert!(matches!(plan(&offers, "X1-A", &config), Plan::Wait));
+
+        config.asteroid = Some("X1-A-2".to_string());
+        assert!(matches!(
+            plan(&offers, "X1-A", &config),
+            Plan::Accept(status) if status.contract.id == "here"
+        ));
+    }
+}
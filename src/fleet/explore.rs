@@ -0,0 +1,365 @@
+use std::{collections::HashSet, time::Duration};
+
+use serde::Deserialize;
+use tracing::{event, Level};
+
+use super::{
+    context::{cooldown, sells_fuel},
+    Behavior, ShipContext, Step, StepFuture,
+};
+use crate::{
+    model::{Market, MountType, Ship, System, Waypoint, WaypointTraitSymbol, WaypointType},
+    store::Store,
+};
+
+/// How long to wait before looking again when everything within reach is explored.
+const EXPLORED_WAIT: Duration = Duration::from_secs(3600);
+
+const PAGE_SIZE: u64 = 20;
+
+/// Settings of the exploring behavior.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct ExploringConfig {
+    /// Warp to the closest unexplored system within fuel range when no jump gate leads anywhere new.
+    pub warp: bool,
+}
+
+/// Charts every uncharted waypoint of a system, then moves on to the next system
+/// that still has uncharted waypoints or that we know nothing about.
+///
+/// Ships with a sensor array scan each system they arrive in for waypoints and systems
+/// the map is missing. Everything found is recorded by the store.
+///
+/// The ship refuels wherever it comes across a market that sells fuel,
+/// and only heads for waypoints the fuel in its tank can reach.
+#[derive(Debug)]
+pub struct Exploring {
+    config: ExploringConfig,
+    /// Systems scanned for waypoints since the behavior started.
+    scanned_waypoints: HashSet<String>,
+    /// Systems scanned from for other systems since the behavior started.
+    scanned: HashSet<String>,
+}
+
+impl Exploring {
+    pub fn new(config: ExploringConfig) -> Self {
+        Self {
+            config,
+            scanned_waypoints: HashSet::new(),
+            scanned: HashSet::new(),
+        }
+    }
+
+    async fn next(&mut self, ctx: &mut ShipContext) -> Result<Step, anyhow::Error> {
+        let ship = ctx.ship().await?;
+        let system = &ship.nav.system_symbol;
+        let here = &ship.nav.waypoint_symbol;
+
+        let waypoints = waypoints(ctx.store(), system)?;
+        if waypoints.is_empty() {
+            map_system(ctx, system).await?;
+            return Ok(Step::Continue);
+        }
+
+        if ship.fuel.current < ship.fuel.capacity && fuel_here(ctx, &waypoints, here).await? {
+            ctx.dock(&ship).await?;
+            let market = ctx.market(&ship).await?;
+            ctx.refuel(&ship, &market).await?;
+            return Ok(Step::Continue);
+        }
+
+        if waypoints
+            .iter()
+            .any(|w| &w.symbol == here && w.chart.is_none())
+        {
+            // Someone else may have charted it since we last saw it.
+            let waypoint = ctx.client().await.get_waypoint(here.clone()).await?;
+            if waypoint.chart.is_none() {
+                ctx.client().await.create_chart(ctx.symbol.clone()).await?;
+                event!(Level::INFO, "Charted {here}");
+            }
+            return Ok(Step::Continue);
+        }
+
+        if can_scan(&ship) && !self.scanned.contains(system) {
+            if let Some(wait) = cooldown(&ship)? {
+                return Ok(Step::Wait(wait));
+            }
+            if self.scanned_waypoints.insert(system.clone()) {
+                scan_waypoints(ctx, system, &waypoints).await?;
+            } else {
+                scan_systems(ctx).await?;
+                self.scanned.insert(system.clone());
+            }
+            return Ok(Step::Continue);
+        }
+
+        if let Some(waypoint) = nearest_uncharted(&waypoints, here, fuel(&ship)) {
+            ctx.navigate(&ship, &waypoint.symbol).await?;
+            return Ok(Step::Continue);
+        }
+
+        self.leave(ctx, &ship, &waypoints).await
+    }
+
+    /// Jump or warp to a system that still needs exploring.
+    async fn leave(
+        &self,
+        ctx: &ShipContext,
+        ship: &Ship,
+        waypoints: &[Waypoint],
+    ) -> Result<Step, anyhow::Error> {
+        let gate = waypoints
+            .iter()
+            .find(|w| w.waypoint_type == WaypointType::JumpGate && !w.is_under_construction);
+        if let Some(gate) = gate {
+            let connections = ctx
+                .client()
+                .await
+                .get_jumpgate(gate.symbol.clone())
+                .await?
+                .connections;
+            let mut destination = None;
+            for connection in connections {
+                if unexplored(ctx.store(), system_of(&connection))? {
+                    destination = Some(connection);
+                    break;
+                }
+            }
+
+            let reachable = reachable(waypoints, &ship.nav.waypoint_symbol, gate, fuel(ship));
+            if let Some(destination) = destination.filter(|_| reachable) {
+                if ship.nav.waypoint_symbol != gate.symbol {
+                    ctx.navigate(ship, &gate.symbol).await?;
+                    return Ok(Step::Continue);
+                }
+                if let Some(wait) = cooldown(ship)? {
+                    return Ok(Step::Wait(wait));
+                }
+                ctx.orbit(ship).await?;
+                ctx.client()
+                    .await
+                    .jump_ship(ctx.symbol.clone(), destination.clone())
+                    .await?;
+                event!(Level::INFO, "Jumped to {destination}");
+                return Ok(Step::Continue);
+            }
+        }
+
+        if self.config.warp {
+            let systems = ctx.store().all::<System>()?;
+            let systems: Vec<_> = systems.into_iter().map(|s| s.value).collect();
+            for system in within_range(&systems, &ship.nav.system_symbol, ship.fuel.current) {
+                let Some(arrival) = system.waypoints.first() else {
+                    continue;
+                };
+                if unexplored(ctx.store(), &system.symbol.0)? {
+                    ctx.orbit(ship).await?;
+                    ctx.client()
+                        .await
+                        .warp_ship(ctx.symbol.clone(), arrival.symbol.clone())
+                        .await?;
+                    event!(Level::INFO, "Warping to {}", system.symbol.0);
+                    return Ok(Step::Continue);
+                }
+            }
+        }
+
+        event!(
+            Level::INFO,
+            "Explored everything within reach of {}",
+            ship.nav.system_symbol
+        );
+        Ok(Step::Wait(EXPLORED_WAIT))
+    }
+}
+
+impl Behavior for Exploring {
+    fn step<'a>(&'a mut self, ship: &'a mut ShipContext) -> StepFuture<'a> {
+        Box::pin(self.next(ship))
+    }
+}
+
+fn waypoints(store: &Store, system: &str) -> Result<Vec<Waypoint>, anyhow::Error> {
+    Ok(store
+        .waypoints_in_system(system)?
+        .into_iter()
+        .map(|w| w.value)
+        .collect())
+}
+
+/// Whether we know nothing about the waypoints of a system, or some are uncharted.
+fn unexplored(store: &Store, system: &str) -> Result<bool, anyhow::Error> {
+    let waypoints = waypoints(store, system)?;
+    Ok(waypoints.is_empty() || waypoints.iter().any(|w| w.chart.is_none()))
+}
+
+fn system_of(waypoint_symbol: &str) -> &str {
+    waypoint_symbol
+        .rsplit_once('-')
+        .map_or(waypoint_symbol, |(system, _)| system)
+}
+
+/// The fuel the ship has to get anywhere, or `None` if it does not need any.
+fn fuel(ship: &Ship) -> Option<u64> {
+    (ship.fuel.capacity > 0).then_some(ship.fuel.current)
+}
+
+/// Whether the waypoint the ship is at has a market that sells fuel.
+/// Markets we know nothing about yet are fetched, which records them.
+async fn fuel_here(
+    ctx: &ShipContext,
+    waypoints: &[Waypoint],
+    here: &str,
+) -> Result<bool, anyhow::Error> {
+    let has_market = waypoints.iter().any(|w| {
+        w.symbol == here
+            && w.traits
+                .iter()
+                .any(|t| t.symbol == WaypointTraitSymbol::Marketplace)
+    });
+    if !has_market {
+        return Ok(false);
+    }
+    let market = match ctx.store().get::<Market>(here)? {
+        Some(market) => market.value,
+        None => ctx.client().await.get_market(here.to_string()).await?,
+    };
+    Ok(sells_fuel(&market))
+}
+
+fn position(waypoints: &[Waypoint], symbol: &str) -> (i64, i64) {
+    waypoints
+        .iter()
+        .find(|w| w.symbol == symbol)
+        .map_or((0, 0), |w| (w.x, w.y))
+}
+
+fn squared_distance(a: (i64, i64), b: &Waypoint) -> i64 {
+    (b.x - a.0).pow(2) + (b.y - a.1).pow(2)
+}
+
+/// Whether cruising from `here` to `to` takes no more than `fuel`.
+fn reachable(waypoints: &[Waypoint], here: &str, to: &Waypoint, fuel: Option<u64>) -> bool {
+    let Some(fuel) = fuel else {
+        return true;
+    };
+    let distance = (squared_distance(position(waypoints, here), to) as f64).sqrt();
+    distance.round() as u64 <= fuel
+}
+
+/// The closest uncharted waypoint that `fuel` can reach.
+fn nearest_uncharted<'a>(
+    waypoints: &'a [Waypoint],
+    here: &str,
+    fuel: Option<u64>,
+) -> Option<&'a Waypoint> {
+    let position = position(waypoints, here);
+    waypoints
+        .iter()
+        .filter(|w| w.chart.is_none())
+        .filter(|w| reachable(waypoints, here, w, fuel))
+        .min_by_key(|w| squared_distance(position, w))
+}
+
+/// The other systems at most `range` away from `from`, the closest first.
+fn within_range<'a>(systems: &'a [System], from: &str, range: u64) -> Vec<&'a System> {
+    let Some(origin) = systems.iter().find(|s| s.symbol.0 == from) else {
+        return Vec::new();
+    };
+    let distance = |s: &System| (s.x - origin.x).pow(2) + (s.y - origin.y).pow(2);
+    let mut systems: Vec<_> = systems
+        .iter()
+        .filter(|s| s.symbol.0 != from && distance(s) <= (range * range) as i64)
+        .collect();
+    systems.sort_by_key(|s| distance(s));
+    systems
+}
+
+fn can_scan(ship: &Ship) -> bool {
+    ship.mounts.iter().any(|mount| {
+        matches!(
+            mount.symbol,
+            MountType::MountSensorArrayI
+                | MountType::MountSensorArrayIi
+                | MountType::MountSensorArrayIii
+        )
+    })
+}
+
+/// Scan for waypoints, and map the system again if the map is missing any of them.
+async fn scan_waypoints(
+    ctx: &ShipContext,
+    system: &str,
+    known: &[Waypoint],
+) -> Result<(), anyhow::Error> {
+    let (_, found) = ctx
+        .client()
+        .await
+        .scan_waypoints(ctx.symbol.clone())
+        .await?;
+    if found
+        .iter()
+        .any(|scanned| !known.iter().any(|w| w.symbol == scanned.symbol))
+    {
+        map_system(ctx, system).await?;
+    }
+    Ok(())
+}
+
+/// Scan for systems, and fetch the ones the map is missing.
+async fn scan_systems(ctx: &ShipContext) -> Result<(), anyhow::Error> {
+    let (_, systems) = ctx.client().await.scan_systems(ctx.symbol.clone()).await?;
+    for system in systems {
+        if ctx.store().get::<System>(&system.symbol)?.is_none() {
+            ctx.client().await.get_system(system.symbol).await?;
+        }
+    }
+    Ok(())
+}
+
+/// List all waypoints of a system, which the store records as they come in.
+async fn map_system(ctx: &ShipContext, system: &str) -> Result<(), anyhow::Error> {
+    for page in 1.. {
+        let (waypoints, meta) = ctx
+            .client()
+            .await
+            .list_waypoints(system.to_string(), Some(PAGE_SIZE), Some(page), None, None)
+            .await?;
+        if waypoints.is_empty() || page * PAGE_SIZE >= meta.total {
+            break;
+        }
+    }
+    event!(Level::DEBUG, "Mapped the waypoints of {system}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::fixtures;
+
+    #[test]
+    fn charts_the_closest_uncharted_waypoint_next() {
+        let mut here = fixtures::waypoint("X1-A-1", 0, 0);
+        here.chart = Some(serde_json::from_value(serde_json::json!({})).unwrap());
+        let mut charted = fixtures::waypoint("X1-A-2", 1, 0);
+        charted.chart = here.chart.clone();
+        let waypoints = [
+            here,
+            charted,
+            fixtures::waypoint("X1-A-3", 10, 10),
+            fixtures::waypoint("X1-A-4", -5, 5),
+        ];
+
+        let next = nearest_uncharted(&waypoints, "X1-A-1", None).unwrap();
+        assert_eq!(next.symbol, "X1-A-4");
+
+        assert!(nearest_uncharted(&waypoints[..2], "X1-A-1", None).is_none());
+
+        let next = nearest_uncharted(&waypoints, "X1-A-1", Some(20)).unwrap();
+        assert_eq!(next.symbol, "X1-A-4");
+        assert!(nearest_uncharted(&waypoints, "X1-A-1", Some(5)).is_none());
+    }
+}
@@ -0,0 +1,218 @@
+use std::{collections::HashMap, time::Duration};
+
+use serde::{de::Error, Deserialize, Deserializer};
+use tokio::sync::Mutex;
+use tracing::{event, Level};
+
+use super::{context::worn, Behavior, ShipContext, Step, StepFuture};
+use crate::model::{Ship, ShipNavStatus};
+
+/// How often a parked hauler checks how full it is.
+const PARKED_POLL: Duration = Duration::from_secs(30);
+
+fn default_depart_at() -> f64 {
+    0.9
+}
+
+/// Settings of the hauling behavior.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct HaulingConfig {
+    /// The waypoint to park at, usually an asteroid or gas giant.
+    pub waypoint: String,
+    /// How full the hold has to be before the hauler leaves to sell,
+    /// above 0 and at most 1.
+    #[serde(
+        default = "default_depart_at",
+        deserialize_with = "deserialize_depart_at"
+    )]
+    pub depart_at: f64,
+}
+
+/// Reject thresholds a hauler would never or always depart at, including NaN.
+fn deserialize_depart_at<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    let depart_at = f64::deserialize(deserializer)?;
+    if depart_at > 0.0 && depart_at <= 1.0 {
+        Ok(depart_at)
+    } else {
+        Err(D::Error::custom(format!(
+            "depart_at must be above 0 and at most 1, got {depart_at}"
+        )))
+    }
+}
+
+/// The haulers that are parked somewhere, waiting for cargo.
+///
+/// Ships that want to get rid of their cargo hand it over through this,
+/// so that two ships never pick the same free space at the same time.
+#[derive(Debug, Default)]
+pub struct Haulers {
+    /// The waypoint each parked hauler is at.
+    parked: Mutex<HashMap<String, String>>,
+}
+
+impl Haulers {
+    async fn park(&self, hauler: &str, waypoint: &str) {
+        self.parked
+            .lock()
+            .await
+            .insert(hauler.to_string(), waypoint.to_string());
+    }
+
+    async fn depart(&self, hauler: &str) {
+        self.parked.lock().await.remove(hauler);
+    }
+
+    /// Hand as much of the ship's cargo as fits to the parked haulers at its waypoint,
+    /// the emptiest one first. Returns whether any hauler took anything.
+    pub async fn hand_over(&self, ctx: &ShipContext, ship: &Ship) -> Result<bool, anyhow::Error> {
+        let parked = self.parked.lock().await;
+        let mut haulers = available(&parked, ship, |symbol| ctx.other_ship(symbol));
+        if haulers.is_empty() {
+            return Ok(false);
+        }
+
+        let mut items = ship
+            .cargo
+            .inventory
+            .iter()
+            .map(|item| (item.symbol, item.units));
+        let mut item = items.next();
+        while let (Some((good, units)), Some(hauler)) = (item, haulers.first_mut()) {
+            let space = hauler.cargo.capacity - hauler.cargo.units;
+            let batch = units.min(space);
+            ctx.transfer(good, batch, &hauler.symbol).await?;
+            event!(
+                Level::DEBUG,
+                "Handed {batch} units of {good:?} to `{}`",
+                hauler.symbol
+            );
+
+            hauler.cargo.units += batch;
+            if hauler.cargo.units >= hauler.cargo.capacity {
+                haulers.remove(0);
+            }
+            item = if batch < units {
+                Some((good, units - batch))
+            } else {
+                items.next()
+            };
+        }
+
+        Ok(true)
+    }
+}
+
+/// The parked haulers that are in orbit of the ship's waypoint and have room left,
+/// the emptiest first.
+fn available(
+    parked: &HashMap<String, String>,
+    ship: &Ship,
+    lookup: impl Fn(&str) -> Option<Ship>,
+) -> Vec<Ship> {
+    let mut haulers: Vec<_> = parked
+        .iter()
+        .filter(|(hauler, waypoint)| {
+            **waypoint == ship.nav.waypoint_symbol && **hauler != ship.symbol
+        })
+        .filter_map(|(hauler, _)| lookup(hauler))
+        .filter(|hauler| {
+            hauler.nav.waypoint_symbol == ship.nav.waypoint_symbol
+                && hauler.nav.status == ShipNavStatus::InOrbit
+                && hauler.cargo.units < hauler.cargo.capacity
+        })
+        .collect();
+    haulers.sort_by_key(|hauler| hauler.cargo.units);
+    haulers
+}
+
+/// Parks at a waypoint and takes cargo from the ships working it,
+/// then sells everything once the hold is full enough, and comes back.
+#[derive(Debug)]
+pub struct Hauling {
+    config: HaulingConfig,
+}
+
+impl Hauling {
+    pub fn new(config: HaulingConfig) -> Self {
+        Self { config }
+    }
+
+    async fn next(&mut self, ctx: &mut ShipContext) -> Result<Step, anyhow::Error> {
+        let ship = ctx.ship().await?;
+        let waypoint = &self.config.waypoint;
+        let full = ship.cargo.units as f64 >= ship.cargo.capacity as f64 * self.config.depart_at;
+
+        if worn(&ship) || full {
+            ctx.haulers().depart(&ctx.symbol).await;
+        }
+        if worn(&ship) {
+            return ctx.repair(&ship).await;
+        }
+        if full {
+            return ctx.sell_at_best_market(&ship).await;
+        }
+
+        if &ship.nav.waypoint_symbol != waypoint {
+            ctx.navigate(&ship, waypoint).await?;
+            return Ok(Step::Continue);
+        }
+        ctx.orbit(&ship).await?;
+        ctx.haulers().park(&ctx.symbol, waypoint).await;
+
+        Ok(Step::Wait(PARKED_POLL))
+    }
+}
+
+impl Behavior for Hauling {
+    fn step<'a>(&'a mut self, ship: &'a mut ShipContext) -> StepFuture<'a> {
+        Box::pin(self.next(ship))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::fixtures;
+
+    fn hauler(symbol: &str, waypoint: &str, units: u64, status: ShipNavStatus) -> Ship {
+        let mut ship = fixtures::ship(symbol, waypoint);
+        ship.cargo.capacity = 40;
+        ship.cargo.units = units;
+        ship.nav.status = status;
+        ship
+    }
+
+    #[test]
+    fn prefers_the_emptiest_hauler_at_the_same_waypoint() {
+        let ships = [
+            hauler("HAUL-1", "X1-A-1", 20, ShipNavStatus::InOrbit),
+            hauler("HAUL-2", "X1-A-1", 5, ShipNavStatus::InOrbit),
+            hauler("HAUL-3", "X1-A-1", 40, ShipNavStatus::InOrbit),
+            hauler("HAUL-4", "X1-A-1", 0, ShipNavStatus::Docked),
+            hauler("HAUL-5", "X1-A-2", 0, ShipNavStatus::InOrbit),
+        ];
+        let parked = ships
+            .iter()
+            .map(|ship| (ship.symbol.clone(), ship.nav.waypoint_symbol.clone()))
+            .collect();
+        let miner = fixtures::ship("MINER-1", "X1-A-1");
+
+        let haulers = available(&parked, &miner, |symbol| {
+            ships.iter().find(|ship| ship.symbol == symbol).cloned()
+        });
+
+        let symbols: Vec<_> = haulers.iter().map(|ship| ship.symbol.as_str()).collect();
+        assert_eq!(symbols, ["HAUL-2", "HAUL-1"]);
+    }
+
+    #[test]
+    fn rejects_thresholds_outside_of_the_hold() {
+        let config: HaulingConfig = toml::from_str("waypoint = 'X1-A-1'").unwrap();
+        assert_eq!(config.depart_at, 0.9);
+
+        for depart_at in ["0.0", "1.5", "nan", "-1.0"] {
+            let toml = format!("waypoint = 'X1-A-1'\ndepart_at = {depart_at}");
+            assert!(toml::from_str::<HaulingConfig>(&toml).is_err());
+        }
+    }
+}
@@ -0,0 +1,13 @@
+use std::time::Duration;
+
+use super::{Behavior, ShipContext, Step, StepFuture};
+
+/// Leaves the ship alone.
+#[derive(Debug)]
+pub struct Idle;
+
+impl Behavior for Idle {
+    fn step<'a>(&'a mut self, _ship: &'a mut ShipContext) -> StepFuture<'a> {
+        Box::pin(async { Ok(Step::Wait(Duration::from_secs(60 * 60))) })
+    }
+}
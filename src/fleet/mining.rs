@@ -0,0 +1,181 @@
+use serde::Deserialize;
+use tracing::{event, Level};
+
+use super::{
+    context::{cooldown, worn},
+    Behavior, ShipContext, Step, StepFuture,
+};
+use crate::model::{MountType, Ship, TradeSymbol};
+
+/// Settings of the mining behavior.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MiningConfig {
+    /// The waypoint to mine.
+    pub asteroid: String,
+    /// If not empty, everything else that is extracted is jettisoned.
+    #[serde(default)]
+    pub keep: Vec<TradeSymbol>,
+    /// Goods that are jettisoned as soon as they were extracted.
+    #[serde(default)]
+    pub jettison: Vec<TradeSymbol>,
+}
+
+impl MiningConfig {
+    fn keeps(&self, good: TradeSymbol) -> bool {
+        !self.jettison.contains(&good) && (self.keep.is_empty() || self.keep.contains(&good))
+    }
+}
+
+/// Extracts at an asteroid until the cargo hold is full, then hands everything
+/// to the haulers parked there, or if there are none, sells it at the market
+/// that pays the most for it, refuels, and goes back.
+///
+/// The best known survey of the asteroid is used for each extraction.
+/// Ships with a surveyor mount create new surveys when there are none.
+#[derive(Debug)]
+pub struct Mining {
+    config: MiningConfig,
+}
+
+impl Mining {
+    pub fn new(config: MiningConfig) -> Self {
+        Self { config }
+    }
+
+    async fn next(&mut self, ctx: &mut ShipContext) -> Result<Step, anyhow::Error> {
+        let ship = ctx.ship().await?;
+
+        // Throw out what we don't want first, so it does not take up space.
+        if let Some(item) = ship
+            .cargo
+            .inventory
+            .iter()
+            .find(|item| !self.config.keeps(item.symbol))
+        {
+            ctx.jettison(item.symbol, item.units).await?;
+            return Ok(Step::Continue);
+        }
+
+        if worn(&ship) {
+            ctx.repair(&ship).await
+        } else if ship.cargo.units < ship.cargo.capacity {
+            self.mine(ctx, &ship).await
+        } else if ctx.haulers().hand_over(ctx, &ship).await? {
+            Ok(Step::Continue)
+        } else {
+            ctx.sell_at_best_market(&ship).await
+        }
+    }
+
+    pub(super) async fn mine(&self, ctx: &ShipContext, ship: &Ship) -> Result<Step, anyhow::Error> {
+        let asteroid = &self.config.asteroid;
+        if &ship.nav.waypoint_symbol != asteroid {
+            ctx.navigate(ship, asteroid).await?;
+            return Ok(Step::Continue);
+        }
+        ctx.orbit(ship).await?;
+        if let Some(wait) = cooldown(ship)? {
+            return Ok(Step::Wait(wait));
+        }
+
+        let survey = ctx.store().ranked_surveys(asteroid)?.into_iter().next();
+        let symbol = ctx.symbol.clone();
+        let (_, extraction, _, events) = match survey {
+            Some(ranked) => {
+                ctx.client()
+                    .await
+                    .extract_resources_with_survey(symbol, ranked.survey)
+                    .await?
+            }
+            None if can_survey(ship) => {
+                // The surveys are stored as they come in, so the next step will find them.
+                ctx.client().await.create_survey(symbol).await?;
+                return Ok(Step::Continue);
+            }
+            None => ctx.client().await.extract_resources(symbol).await?,
+        };
+        ctx.conditions(&events);
+        event!(
+            Level::DEBUG,
+            "Extracted {} units of {:?}",
+            extraction.extraction_yield.units,
+            extraction.extraction_yield.symbol
+        );
+
+        Ok(Step::Continue)
+    }
+}
+
+impl Behavior for Mining {
+    fn step<'a>(&'a mut self, ship: &'a mut ShipContext) -> StepFuture<'a> {
+        Box::pin(self.next(ship))
+    }
+}
+
+fn can_survey(ship: &Ship) -> bool {
+    ship.mounts.iter().any(|mount| {
+        matches!(
+            mount.symbol,
+            MountType::MountSurveyorI | MountType::MountSurveyorIi | MountType::MountSurveyorIii
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fleet::context::best_market, model::fixtures, store::Store};
+
+    #[test]
+    fn keeps_only_wanted_goods() {
+        let mut config = MiningConfig {
+            asteroid: "X1-A-1".to_string(),
+            keep: Vec::new(),
+            jettison: vec![TradeSymbol::IceWater],
+        };
+        assert!(config.keeps(TradeSymbol::IronOre));
+        assert!(!config.keeps(TradeSymbol::IceWater));
+
+        config.keep = vec![TradeSymbol::PreciousStones];
+        assert!(!config.keeps(TradeSymbol::IronOre));
+        assert!(config.keeps(TradeSymbol::PreciousStones));
+    }
+
+    #[test]
+    fn sells_where_the_cargo_is_worth_the_most() {
+        let store = Store::open_in_memory().unwrap();
+        for (waypoint, iron, copper) in [("X1-A-2", 40, 10), ("X1-A-3", 30, 50)] {
+            let market = serde_json::from_value(serde_json::json!({
+                "symbol": waypoint,
+                "exports": [],
+                "imports": [],
+                "exchange": [],
+                "tradeGoods": [
+                    { "symbol": "IRON_ORE", "type": "IMPORT", "tradeVolume": 10,
+                      "supply": "SCARCE", "purchasePrice": 80, "sellPrice": iron },
+                    { "symbol": "COPPER_ORE", "type": "IMPORT", "tradeVolume": 10,
+                      "supply": "SCARCE", "purchasePrice": 80, "sellPrice": copper }
+                ]
+            }))
+            .unwrap();
+            store.record_prices(&market).unwrap();
+        }
+        let mut ship = fixtures::ship("CAT-1", "X1-A-1");
+        assert_eq!(best_market(&store, &ship).unwrap(), None);
+
+        ship.cargo = serde_json::from_value(serde_json::json!({
+            "capacity": 15,
+            "units": 15,
+            "inventory": [
+                { "symbol": "IRON_ORE", "name": "", "description": "", "units": 10 },
+                { "symbol": "COPPER_ORE", "name": "", "description": "", "units": 5 }
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            best_market(&store, &ship).unwrap().as_deref(),
+            Some("X1-A-3")
+        );
+    }
+}
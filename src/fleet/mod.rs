@@ -0,0 +1,512 @@
+//! The fleet runtime: one long-running task per ship, each driven by a [`Behavior`].
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex as SyncMutex},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::{
+    sync::{watch, Mutex},
+    task::JoinHandle,
+    time::{interval, sleep, MissedTickBehavior},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{event, instrument, Instrument, Level};
+use utoipa::ToSchema;
+
+use crate::{
+    cache::Cache,
+    client::{self, Client},
+    config::FleetConfig,
+    model::Ship,
+    store::Store,
+};
+use construct::Constructing;
+use context::ShipContext;
+use contract::Contracting;
+use explore::Exploring;
+use haul::{Haulers, Hauling};
+use idle::Idle;
+use mining::Mining;
+use scout::{Scouting, Scouts};
+use siphon::Siphon;
+use trade::Trading;
+
+pub use construct::ConstructingConfig;
+pub use contract::ContractingConfig;
+pub use explore::ExploringConfig;
+pub use haul::HaulingConfig;
+pub use mining::MiningConfig;
+pub use scout::ScoutingConfig;
+pub use siphon::SiphonConfig;
+pub use trade::TradingConfig;
+
+mod construct;
+mod context;
+mod contract;
+mod explore;
+mod haul;
+mod idle;
+mod mining;
+mod scout;
+mod siphon;
+mod trade;
+
+/// The name the symbols of paused ships are saved under.
+const PAUSED: &str = "fleet_paused";
+/// How long a ship waits before starting over after its behavior failed.
+/// This doubles with every failure in a row, up to [`MAX_BACKOFF`].
+const MIN_BACKOFF: Duration = Duration::from_secs(5);
+const MAX_BACKOFF: Duration = Duration::from_secs(5 * 60);
+
+/// What to do after a step of a behavior.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// Take the next step right away.
+    Continue,
+    /// Take the next step after this long, unless the ship is paused in the meantime.
+    Wait(Duration),
+}
+
+pub type StepFuture<'a> = Pin<Box<dyn Future<Output = Result<Step, anyhow::Error>> + Send + 'a>>;
+
+/// Decides what a ship does, one step at a time.
+///
+/// The runtime waits for the ship to arrive before each step, so behaviors never
+/// see a ship in transit. Everything else, like cooldowns and cargo, is up to them.
+/// If a step fails, the behavior is thrown away and a new one is created after a while,
+/// so behaviors should be able to pick up where the ship is at.
+pub trait Behavior: Send {
+    fn step<'a>(&'a mut self, ship: &'a mut ShipContext) -> StepFuture<'a>;
+}
+
+/// The behaviors ships can be assigned.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BehaviorKind {
+    Idle,
+    Mining,
+    Siphon,
+    Hauling,
+    Trading,
+    Contracting,
+    Scouting,
+    Exploring,
+    Constructing,
+}
+
+impl BehaviorKind {
+    /// The name requests are recorded under, for example in the ledger.
+    fn task_name(self) -> &'static str {
+        match self {
+            BehaviorKind::Idle => "idle",
+            BehaviorKind::Mining => "mining",
+            BehaviorKind::Siphon => "siphon",
+            BehaviorKind::Hauling => "hauling",
+            BehaviorKind::Trading => "trading",
+            BehaviorKind::Contracting => "contracting",
+            BehaviorKind::Scouting => "scouting",
+            BehaviorKind::Exploring => "exploring",
+            BehaviorKind::Constructing => "constructing",
+        }
+    }
+}
+
+/// A behavior along with its settings, as assigned to a ship in the configuration.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case", tag = "behavior")]
+pub enum BehaviorConfig {
+    /// Do nothing at all.
+    Idle,
+    /// Mine an asteroid and sell what was extracted.
+    Mining(MiningConfig),
+    /// Siphon a gas giant and sell the gas or hand it to a hauler.
+    Siphon(SiphonConfig),
+    /// Park at a waypoint, take the cargo of the ships working it, and sell it.
+    Hauling(HaulingConfig),
+    /// Buy goods where they are cheap and sell them where they are not.
+    Trading(TradingConfig),
+    /// Negotiate, source, deliver and fulfill procurement contracts.
+    Contracting(ContractingConfig),
+    /// Keep the market and shipyard data of the system fresh.
+    Scouting(ScoutingConfig),
+    /// Chart uncharted waypoints and move on to unexplored systems.
+    Exploring(ExploringConfig),
+    /// Buy and deliver the materials a construction site needs.
+    Constructing(ConstructingConfig),
+}
+
+impl BehaviorConfig {
+    fn kind(&self) -> BehaviorKind {
+        match self {
+            BehaviorConfig::Idle => BehaviorKind::Idle,
+            BehaviorConfig::Mining(_) => BehaviorKind::Mining,
+            BehaviorConfig::Siphon(_) => BehaviorKind::Siphon,
+            BehaviorConfig::Hauling(_) => BehaviorKind::Hauling,
+            BehaviorConfig::Trading(_) => BehaviorKind::Trading,
+            BehaviorConfig::Contracting(_) => BehaviorKind::Contracting,
+            BehaviorConfig::Scouting(_) => BehaviorKind::Scouting,
+            BehaviorConfig::Exploring(_) => BehaviorKind::Exploring,
+            BehaviorConfig::Constructing(_) => BehaviorKind::Constructing,
+        }
+    }
+
+    fn create(&self) -> Box<dyn Behavior> {
+        match self {
+            BehaviorConfig::Idle => Box::new(Idle),
+            BehaviorConfig::Mining(config) => Box::new(Mining::new(config.clone())),
+            BehaviorConfig::Siphon(config) => Box::new(Siphon::new(config.clone())),
+            BehaviorConfig::Hauling(config) => Box::new(Hauling::new(config.clone())),
+            BehaviorConfig::Trading(config) => Box::new(Trading::new(config.clone())),
+            BehaviorConfig::Contracting(config) => Box::new(Contracting::new(config.clone())),
+            BehaviorConfig::Scouting(config) => Box::new(Scouting::new(config.clone())),
+            BehaviorConfig::Exploring(config) => Box::new(Exploring::new(config.clone())),
+            BehaviorConfig::Constructing(config) => Box::new(Constructing::new(config.clone())),
+        }
+    }
+
+    /// The behavior the configuration assigns to `ship`, by its symbol or else its role.
+    fn assigned(config: &FleetConfig, ship: &Ship) -> Self {
+        config
+            .ships
+            .get(&ship.symbol)
+            .or_else(|| config.roles.get(&ship.registration.role))
+            .cloned()
+            .unwrap_or(BehaviorConfig::Idle)
+    }
+}
+
+/// What the task of a ship is up to.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum Activity {
+    Working,
+    InTransit,
+    Waiting,
+    Paused,
+    /// The behavior failed and is restarted after a while.
+    BackingOff,
+}
+
+/// The state of one ship's task.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ShipStatus {
+    pub symbol: String,
+    pub behavior: BehaviorKind,
+    pub activity: Activity,
+    /// How often the behavior failed and was started over.
+    pub restarts: u32,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug)]
+struct Handle {
+    status: ShipStatus,
+    task: JoinHandle<()>,
+}
+
+/// The ships that are driven by the runtime, and which of them are paused.
+///
+/// Paused ships finish their current step and then wait until they are resumed.
+/// Which ships are paused is saved in the store, so it survives restarts.
+#[derive(Debug)]
+pub struct Fleet {
+    ships: SyncMutex<HashMap<String, Handle>>,
+    paused: watch::Sender<BTreeSet<String>>,
+    store: Arc<Store>,
+}
+
+impl Fleet {
+    pub fn new(store: Arc<Store>) -> Result<Self, anyhow::Error> {
+        let paused = store.job_state(PAUSED)?.unwrap_or_default();
+
+        Ok(Self {
+            ships: SyncMutex::default(),
+            paused: watch::Sender::new(paused),
+            store,
+        })
+    }
+
+    /// The state of every ship with a running task, ordered by symbol.
+    pub fn statuses(&self) -> Vec<ShipStatus> {
+        let mut statuses: Vec<_> = self
+            .ships
+            .lock()
+            .unwrap()
+            .values()
+            .map(|handle| handle.status.clone())
+            .collect();
+        statuses.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        statuses
+    }
+
+    /// Pause or resume a ship. Ships that don't have a task yet stay paused once they get one.
+    pub fn set_paused(&self, symbol: &str, paused: bool) -> Result<(), anyhow::Error> {
+        self.paused.send_if_modified(|ships| {
+            if paused {
+                ships.insert(symbol.to_string())
+            } else {
+                ships.remove(symbol)
+            }
+        });
+        self.store.set_job_state(PAUSED, &*self.paused.borrow())
+    }
+
+    fn update(&self, symbol: &str, f: impl FnOnce(&mut ShipStatus)) {
+        if let Some(handle) = self.ships.lock().unwrap().get_mut(symbol) {
+            f(&mut handle.status);
+        }
+    }
+
+    /// Spawn tasks for ships that don't have one, including ships whose task panicked,
+    /// and abort the tasks of ships that are gone.
+    fn reconcile(self: &Arc<Self>, config: &FleetConfig, ships: Vec<Ship>, runtime: &Runtime) {
+        let mut handles = self.ships.lock().unwrap();
+
+        handles.retain(|symbol, handle| {
+            let exists = ships.iter().any(|ship| &ship.symbol == symbol);
+            if !exists {
+                event!(Level::INFO, "Ship `{symbol}` is gone; stopping its task");
+                handle.task.abort();
+            }
+            exists
+        });
+
+        for ship in ships {
+            let restarts = match handles.get(&ship.symbol) {
+                Some(handle) if !handle.task.is_finished() => continue,
+                Some(handle) => {
+                    event!(
+                        Level::WARN,
+                        "Task of ship `{}` died; restarting",
+                        ship.symbol
+                    );
+                    handle.status.restarts + 1
+                }
+                None => 0,
+            };
+
+            let behavior = BehaviorConfig::assigned(config, &ship);
+            event!(
+                Level::INFO,
+                "Starting `{}` with behavior {behavior:?}",
+                ship.symbol
+            );
+            let kind = behavior.kind();
+            let task = tokio::spawn(
+                drive(self.clone(), ship.symbol.clone(), behavior, runtime.clone())
+                    .instrument(tracing::info_span!("ship", symbol = ship.symbol)),
+            );
+            handles.insert(
+                ship.symbol.clone(),
+                Handle {
+                    status: ShipStatus {
+                        symbol: ship.symbol,
+                        behavior: kind,
+                        activity: Activity::Working,
+                        restarts,
+                        last_error: None,
+                    },
+                    task,
+                },
+            );
+        }
+    }
+}
+
+/// What every ship task needs.
+#[derive(Debug, Clone)]
+struct Runtime {
+    client: Arc<Mutex<Client>>,
+    cache: Arc<Cache>,
+    store: Arc<Store>,
+    haulers: Arc<Haulers>,
+    scouts: Arc<Scouts>,
+    shutdown: CancellationToken,
+}
+
+/// List our ships now and then, and make sure each of them has a task driving it.
+/// Ships bought in the meantime are picked up the next time.
+#[instrument(name = "fleet", level = Level::INFO, skip_all)]
+pub async fn run(
+    fleet: Arc<Fleet>,
+    config: FleetConfig,
+    client: Arc<Mutex<Client>>,
+    cache: Arc<Cache>,
+    shutdown: CancellationToken,
+) -> Result<(), anyhow::Error> {
+    let runtime = Runtime {
+        client: client.clone(),
+        cache,
+        store: fleet.store.clone(),
+        haulers: Arc::default(),
+        scouts: Arc::default(),
+        shutdown: shutdown.clone(),
+    };
+    let mut ticker = interval(Duration::from_secs(config.poll_interval_secs.max(1)));
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = ticker.tick() => {}
+        }
+
+        match client.lock().await.list_all_ships().await {
+            Ok(ships) => fleet.reconcile(&config, ships, &runtime),
+            Err(err) => event!(Level::WARN, "Failed to list ships: {err:?}"),
+        }
+    }
+
+    // Let every ship finish its current step.
+    let tasks: Vec<_> = fleet
+        .ships
+        .lock()
+        .unwrap()
+        .drain()
+        .map(|(_, handle)| handle.task)
+        .collect();
+    for task in tasks {
+        let _ = task.await;
+    }
+
+    Ok(())
+}
+
+/// Drive one ship until shutdown, starting its behavior over whenever it fails.
+async fn drive(fleet: Arc<Fleet>, symbol: String, config: BehaviorConfig, runtime: Runtime) {
+    let Runtime {
+        client,
+        cache,
+        store,
+        haulers,
+        scouts,
+        shutdown,
+    } = runtime;
+    let mut paused = fleet.paused.subscribe();
+    let mut ship = ShipContext::new(symbol.clone(), client, cache, store, haulers, scouts);
+    let kind = config.kind();
+    let mut behavior = config.create();
+    let mut backoff = MIN_BACKOFF;
+
+    loop {
+        if paused.borrow_and_update().contains(&symbol) {
+            fleet.update(&symbol, |status| status.activity = Activity::Paused);
+            tokio::select! {
+                _ = shutdown.cancelled() => return,
+                _ = paused.wait_for(|ships| !ships.contains(&symbol)) => {}
+            }
+        }
+
+        let res = async {
+            if let Some(arrival) = ship.arrival().await? {
+                fleet.update(&symbol, |status| status.activity = Activity::InTransit);
+                tokio::select! {
+                    _ = shutdown.cancelled() => return Ok(None),
+                    _ = sleep(arrival) => {}
+                }
+                ship.arrived().await?;
+            }
+
+            fleet.update(&symbol, |status| status.activity = Activity::Working);
+            client::in_task(kind.task_name(), behavior.step(&mut ship))
+                .await
+                .map(Some)
+        }
+        .await;
+
+        let wait = match res {
+            Ok(None) => return,
+            Ok(Some(Step::Continue)) => {
+                backoff = MIN_BACKOFF;
+                continue;
+            }
+            Ok(Some(Step::Wait(wait))) => {
+                backoff = MIN_BACKOFF;
+                fleet.update(&symbol, |status| status.activity = Activity::Waiting);
+                wait
+            }
+            Err(err) => {
+                event!(
+                    Level::WARN,
+                    "Behavior failed; retrying in {backoff:?}: {err:?}"
+                );
+                fleet.update(&symbol, |status| {
+                    status.activity = Activity::BackingOff;
+                    status.restarts += 1;
+                    status.last_error = Some(format!("{err:#}"));
+                });
+                behavior = config.create();
+                let wait = backoff;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+                wait
+            }
+        };
+
+        // Pausing interrupts the wait, so that the ship shows up as paused right away.
+        tokio::select! {
+            _ = shutdown.cancelled() => return,
+            _ = sleep(wait) => {}
+            _ = paused.wait_for(|ships| ships.contains(&symbol)) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pausing_survives_restarts() {
+        let store = Arc::new(Store::open_in_memory().unwrap());
+        let fleet = Fleet::new(store.clone()).unwrap();
+        fleet.set_paused("CAT-1", true).unwrap();
+        fleet.set_paused("CAT-2", true).unwrap();
+        fleet.set_paused("CAT-2", false).unwrap();
+
+        let fleet = Fleet::new(store).unwrap();
+
+        let paused = fleet.paused.borrow();
+        assert!(paused.contains("CAT-1"));
+        assert!(!paused.contains("CAT-2"));
+    }
+
+    #[test]
+    fn assigns_behaviors_from_the_config() {
+        let config: FleetConfig = toml::from_str(
+            r#"
+            [ships.CAT-2]
+            behavior = "mining"
+            asteroid = "X1-A-2"
+            jettison = ["ICE_WATER"]
+
+            [roles.SATELLITE]
+            behavior = "scouting"
+            "#,
+        )
+        .unwrap();
+        let idle = crate::model::fixtures::ship("CAT-1", "X1-A-1");
+        let miner = crate::model::fixtures::ship("CAT-2", "X1-A-1");
+        let mut probe = crate::model::fixtures::ship("CAT-3", "X1-A-1");
+        probe.registration.role = crate::model::ShipRole::Satellite;
+
+        assert_eq!(
+            BehaviorConfig::assigned(&config, &idle),
+            BehaviorConfig::Idle
+        );
+        let BehaviorConfig::Mining(mining) = BehaviorConfig::assigned(&config, &miner) else {
+            panic!("CAT-2 should be mining");
+        };
+        assert_eq!(mining.asteroid, "X1-A-2");
+        assert!(mining.keep.is_empty());
+        assert!(matches!(
+            BehaviorConfig::assigned(&config, &probe),
+            BehaviorConfig::Scouting(_)
+        ));
+    }
+}
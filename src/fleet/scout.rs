@@ -0,0 +1,284 @@
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::Deserialize;
+use tracing::{event, Level};
+
+use super::{Behavior, ShipContext, Step, StepFuture};
+use crate::{
+    model::{Shipyard, WaypointTraitSymbol},
+    store::Store,
+};
+
+fn default_refresh_secs() -> u64 {
+    900
+}
+
+/// Settings of the scouting behavior.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ScoutingConfig {
+    /// How old the data of a market or shipyard may get before it is visited again.
+    #[serde(default = "default_refresh_secs")]
+    pub refresh_secs: u64,
+}
+
+/// The markets and shipyards our probes are headed to or parked at.
+///
+/// Each is claimed by at most one probe, so that probes spread out over a system.
+#[derive(Debug, Default)]
+pub struct Scouts {
+    claims: Mutex<HashMap<String, String>>,
+}
+
+impl Scouts {
+    /// Let `probe` choose given the waypoints the other probes claimed, and claim its choice.
+    fn assign(&self, probe: &str, choose: impl FnOnce(&HashSet<&String>) -> Choice) -> Choice {
+        let mut claims = self.claims.lock().unwrap();
+        let taken = claims
+            .iter()
+            .filter(|(scout, _)| *scout != probe)
+            .map(|(_, waypoint)| waypoint)
+            .collect();
+        let choice = choose(&taken);
+        match &choice {
+            Choice::Visit(waypoint)
+            | Choice::Park {
+                at: Some(waypoint), ..
+            } => claims.insert(probe.to_string(), waypoint.clone()),
+            Choice::Park { at: None, .. } => claims.remove(probe),
+        };
+        choice
+    }
+}
+
+/// A waypoint with a market or shipyard, and how old what we know about it is.
+#[derive(Debug, Clone)]
+struct Target {
+    symbol: String,
+    position: (i64, i64),
+    market: bool,
+    shipyard: bool,
+    /// `None` if it was never seen with a ship present.
+    age: Option<Duration>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Choice {
+    /// Go to the waypoint and refresh it.
+    Visit(String),
+    /// Nothing is stale yet. Stay at the target the probe is at, if it is at one.
+    Park { at: Option<String>, wait: Duration },
+}
+
+/// The stalest target that no other probe claimed, the closest one among equally stale ones.
+fn choose(targets: &[Target], here: &str, taken: &HashSet<&String>, refresh: Duration) -> Choice {
+    let position = targets
+        .iter()
+        .find(|t| t.symbol == here)
+        .map_or((0, 0), |t| t.position);
+    let free: Vec<_> = targets
+        .iter()
+        .filter(|t| !taken.contains(&t.symbol))
+        .collect();
+
+    let stalest = free
+        .iter()
+        .filter(|t| t.age.is_none_or(|age| age >= refresh))
+        .max_by_key(|t| {
+            let distance = (t.position.0 - position.0).pow(2) + (t.position.1 - position.1).pow(2);
+            (t.age.unwrap_or(Duration::MAX), Reverse(distance))
+        });
+    if let Some(target) = stalest {
+        return Choice::Visit(target.symbol.clone());
+    }
+
+    let wait = free
+        .iter()
+        .filter_map(|t| t.age)
+        .map(|age| refresh.saturating_sub(age))
+        .min()
+        .unwrap_or(refresh);
+    let at = free
+        .iter()
+        .find(|t| t.symbol == here)
+        .map(|t| t.symbol.clone());
+    Choice::Park { at, wait }
+}
+
+/// The markets and shipyards of a system. Markets are as old as their latest prices,
+/// shipyards as their latest snapshot listing ships for sale.
+fn targets(store: &Store, system: &str) -> Result<Vec<Target>, anyhow::Error> {
+    let now = SystemTime::now();
+    let mut seen: HashMap<String, SystemTime> = HashMap::new();
+    for record in store.latest_prices(system)? {
+        let at = UNIX_EPOCH + Duration::from_millis(record.recorded_at.max(0) as u64);
+        let entry = seen.entry(record.waypoint_symbol).or_insert(at);
+        *entry = (*entry).max(at);
+    }
+    let shipyards: HashMap<_, _> = store
+        .all::<Shipyard>()?
+        .into_iter()
+        .filter(|shipyard| shipyard.value.ships.is_some())
+        .map(|shipyard| (shipyard.value.symbol, shipyard.fetched_at))
+        .collect();
+
+    let age = |at: Option<&SystemTime>| at.map(|at| now.duration_since(*at).unwrap_or_default());
+    Ok(store
+        .waypoints_in_system(system)?
+        .into_iter()
+        .map(|waypoint| waypoint.value)
+        .filter_map(|waypoint| {
+            let has = |symbol| waypoint.traits.iter().any(|t| t.symbol == symbol);
+            let market = has(WaypointTraitSymbol::Marketplace);
+            let shipyard = has(WaypointTraitSymbol::Shipyard);
+            let ages = [
+                market.then(|| age(seen.get(&waypoint.symbol))),
+                shipyard.then(|| age(shipyards.get(&waypoint.symbol))),
+            ];
+            // The target is as stale as the staler of the two.
+            let age = ages
+                .into_iter()
+                .flatten()
+                .reduce(|a, b| a.zip(b).map(|(a, b)| a.max(b)))?;
+            Some(Target {
+                symbol: waypoint.symbol,
+                position: (waypoint.x, waypoint.y),
+                market,
+                shipyard,
+                age,
+            })
+        })
+        .collect())
+}
+
+/// Keeps the market and shipyard data of a system fresh, with as many probes as there are.
+///
+/// Each probe visits the stalest market or shipyard that no other probe is after.
+/// When everything is fresh, probes park where they are, so that with enough probes
+/// every market has one of its own.
+#[derive(Debug)]
+pub struct Scouting {
+    config: ScoutingConfig,
+}
+
+impl Scouting {
+    pub fn new(config: ScoutingConfig) -> Self {
+        Self { config }
+    }
+
+    async fn next(&mut self, ctx: &mut ShipContext) -> Result<Step, anyhow::Error> {
+        let ship = ctx.ship().await?;
+        let here = &ship.nav.waypoint_symbol;
+        let targets = targets(ctx.store(), &ship.nav.system_symbol)?;
+        let refresh = Duration::from_secs(self.config.refresh_secs);
+
+        let choice = ctx
+            .scouts()
+            .assign(&ctx.symbol, |taken| choose(&targets, here, taken, refresh));
+        let target = match choice {
+            Choice::Visit(target) => target,
+            Choice::Park { wait, .. } => return Ok(Step::Wait(wait)),
+        };
+        if here != &target {
+            ctx.navigate(&ship, &target).await?;
+            return Ok(Step::Continue);
+        }
+
+        let Some(target) = targets.iter().find(|t| t.symbol == target) else {
+            return Ok(Step::Continue);
+        };
+        // Both are recorded as they come in.
+        if target.market {
+            ctx.market(&ship).await?;
+        }
+        if target.shipyard {
+            ctx.client()
+                .await
+                .get_shipyard(target.symbol.clone())
+                .await?;
+        }
+        event!(Level::DEBUG, "Refreshed {}", target.symbol);
+
+        Ok(Step::Continue)
+    }
+}
+
+impl Behavior for Scouting {
+    fn step<'a>(&'a mut self, ship: &'a mut ShipContext) -> StepFuture<'a> {
+        Box::pin(self.next(ship))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(symbol: &str, x: i64, age: Option<u64>) -> Target {
+        Target {
+            symbol: symbol.to_string(),
+            position: (x, 0),
+            market: true,
+            shipyard: false,
+            age: age.map(Duration::from_secs),
+        }
+    }
+
+    #[test]
+    fn visits_the_stalest_market_nobody_else_is_after() {
+        let refresh = Duration::from_secs(900);
+        let targets = [
+            target("X1-A-1", 0, Some(100)),
+            target("X1-A-2", 50, None),
+            target("X1-A-3", 10, None),
+            target("X1-A-4", 20, Some(2000)),
+        ];
+        let taken = "X1-A-3".to_string();
+
+        assert_eq!(
+            choose(&targets, "X1-A-1", &HashSet::new(), refresh),
+            Choice::Visit("X1-A-3".to_string())
+        );
+        assert_eq!(
+            choose(&targets, "X1-A-1", &HashSet::from([&taken]), refresh),
+            Choice::Visit("X1-A-2".to_string())
+        );
+    }
+
+    #[test]
+    fn parks_until_something_gets_stale() {
+        let refresh = Duration::from_secs(900);
+        let targets = [
+            target("X1-A-1", 0, Some(100)),
+            target("X1-A-2", 10, Some(600)),
+        ];
+
+        assert_eq!(
+            choose(&targets, "X1-A-1", &HashSet::new(), refresh),
+            Choice::Park {
+                at: Some("X1-A-1".to_string()),
+                wait: Duration::from_secs(300)
+            }
+        );
+    }
+
+    #[test]
+    fn probes_spread_out() {
+        let scouts = Scouts::default();
+        let refresh = Duration::from_secs(900);
+        let targets = [target("X1-A-1", 0, None), target("X1-A-2", 10, None)];
+
+        let first = scouts.assign("PROBE-1", |taken| {
+            choose(&targets, "X1-A-1", taken, refresh)
+        });
+        let second = scouts.assign("PROBE-2", |taken| {
+            choose(&targets, "X1-A-1", taken, refresh)
+        });
+
+        assert_eq!(first, Choice::Visit("X1-A-1".to_string()));
+        assert_eq!(second, Choice::Visit("X1-A-2".to_string()));
+    }
+}
@@ -0,0 +1,114 @@
+use serde::Deserialize;
+use tracing::{event, Level};
+
+use super::{
+    context::{cooldown, worn},
+    Behavior, ShipContext, Step, StepFuture,
+};
+use crate::model::{Ship, ShipNavStatus};
+
+/// How long to wait for the hauler before looking again.
+const HAULER_POLL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Settings of the siphon behavior.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SiphonConfig {
+    /// The gas giant to siphon.
+    pub gas_giant: String,
+    /// A ship to hand the cargo to once the hold is full, instead of selling it.
+    /// It has to be in orbit of the gas giant; until it is, the drone waits.
+    pub hauler: Option<String>,
+}
+
+/// Siphons gas until the cargo hold is full, then either hands it to its
+/// hauler or any other one parked in orbit, or sells it like a miner would.
+///
+/// Drones with a badly worn component are repaired first.
+#[derive(Debug)]
+pub struct Siphon {
+    config: SiphonConfig,
+}
+
+impl Siphon {
+    pub fn new(config: SiphonConfig) -> Self {
+        Self { config }
+    }
+
+    async fn next(&mut self, ctx: &mut ShipContext) -> Result<Step, anyhow::Error> {
+        let ship = ctx.ship().await?;
+
+        if worn(&ship) {
+            ctx.repair(&ship).await
+        } else if ship.cargo.units < ship.cargo.capacity {
+            self.siphon(ctx, &ship).await
+        } else if let Some(hauler) = &self.config.hauler {
+            self.hand_over(ctx, &ship, hauler).await
+        } else if ctx.haulers().hand_over(ctx, &ship).await? {
+            Ok(Step::Continue)
+        } else {
+            ctx.sell_at_best_market(&ship).await
+        }
+    }
+
+    async fn siphon(&self, ctx: &ShipContext, ship: &Ship) -> Result<Step, anyhow::Error> {
+        let gas_giant = &self.config.gas_giant;
+        if &ship.nav.waypoint_symbol != gas_giant {
+            ctx.navigate(ship, gas_giant).await?;
+            return Ok(Step::Continue);
+        }
+        ctx.orbit(ship).await?;
+        if let Some(wait) = cooldown(ship)? {
+            return Ok(Step::Wait(wait));
+        }
+
+        let (_, siphon, _, events) = ctx
+            .client()
+            .await
+            .siphon_resources(ctx.symbol.clone())
+            .await?;
+        ctx.conditions(&events);
+        event!(
+            Level::DEBUG,
+            "Siphoned {} units of {:?}",
+            siphon.siphon_yield.units,
+            siphon.siphon_yield.symbol
+        );
+
+        Ok(Step::Continue)
+    }
+
+    async fn hand_over(
+        &self,
+        ctx: &ShipContext,
+        ship: &Ship,
+        hauler: &str,
+    ) -> Result<Step, anyhow::Error> {
+        let ready = ctx.other_ship(hauler).filter(|hauler| {
+            hauler.nav.waypoint_symbol == ship.nav.waypoint_symbol
+                && hauler.nav.status == ShipNavStatus::InOrbit
+                && hauler.cargo.units < hauler.cargo.capacity
+        });
+        let Some(hauler) = ready else {
+            event!(Level::DEBUG, "Waiting for `{hauler}` to pick up the cargo");
+            return Ok(Step::Wait(HAULER_POLL));
+        };
+
+        let mut space = hauler.cargo.capacity - hauler.cargo.units;
+        for item in &ship.cargo.inventory {
+            let units = item.units.min(space);
+            if units == 0 {
+                break;
+            }
+            ctx.transfer(item.symbol, units, &hauler.symbol).await?;
+            space -= units;
+        }
+
+        Ok(Step::Continue)
+    }
+}
+
+impl Behavior for Siphon {
+    fn step<'a>(&'a mut self, ship: &'a mut ShipContext) -> StepFuture<'a> {
+        Box::pin(self.next(ship))
+    }
+}
@@ -0,0 +1,435 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use serde::Deserialize;
+use tracing::{event, Level};
+
+use super::{context::worn, Behavior, ShipContext, Step, StepFuture};
+use crate::{
+    model::{Ship, TradeSymbol},
+    store::PriceRecord,
+};
+
+/// How long to wait before looking for a route again when there is none.
+const NO_ROUTE_WAIT: Duration = Duration::from_secs(300);
+
+/// How much prices are assumed to move with every trade volume bought or sold.
+const PRICE_STEP: f64 = 0.05;
+
+/// Units of fuel in one unit of fuel bought at a market.
+const FUEL_PER_UNIT: u64 = 100;
+
+/// Settings of the trading behavior.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct TradingConfig {
+    /// The longest distance the ship travels between two markets.
+    /// Defaults to the ship's fuel capacity.
+    pub range: Option<u64>,
+    /// Routes have to be expected to earn more than this, after fuel.
+    pub min_profit: u64,
+}
+
+/// A good to buy at one market and sell at another.
+#[derive(Debug, Clone, PartialEq)]
+struct Route {
+    good: TradeSymbol,
+    buy_at: String,
+    sell_at: String,
+    units: u64,
+    /// The last known price at the market the good is sold at.
+    sell_price: u64,
+    /// What the trade is expected to earn, after fuel.
+    profit: i64,
+    /// The time spent travelling, from where the ship is to the end of the route.
+    seconds: u64,
+}
+
+/// What a route is planned for.
+#[derive(Debug)]
+struct Trader<'a> {
+    waypoint: &'a str,
+    speed: u64,
+    capacity: u64,
+    /// The longest leg it can fly, or `None` if it does not need fuel.
+    range: Option<f64>,
+    /// The fuel in its tank right now.
+    fuel: u64,
+    credits: u64,
+}
+
+/// The route that earns the most credits per second of travel,
+/// going by the latest known prices of each market.
+///
+/// Prices are assumed to move against us by [`PRICE_STEP`] with every trade volume,
+/// so that large hauls into small markets are not overestimated.
+///
+/// The fuel in the tank has to last to the market the good is bought at. It has to last
+/// for the second leg as well, unless that market sells fuel to refuel with.
+fn plan(
+    prices: &[PriceRecord],
+    positions: &HashMap<&str, (i64, i64)>,
+    trader: &Trader,
+    fuel_price: u64,
+    min_profit: u64,
+) -> Option<Route> {
+    let here = positions.get(trader.waypoint)?;
+    let fuel_markets: HashSet<_> = prices
+        .iter()
+        .filter(|record| record.trade_symbol == TradeSymbol::Fuel)
+        .map(|record| record.waypoint_symbol.as_str())
+        .collect();
+    let mut best: Option<(f64, Route)> = None;
+
+    for buy in prices {
+        let Some(source) = positions.get(buy.waypoint_symbol.as_str()) else {
+            continue;
+        };
+        for sell in prices {
+            if sell.trade_symbol != buy.trade_symbol
+                || sell.waypoint_symbol == buy.waypoint_symbol
+                || sell.sell_price <= buy.purchase_price
+            {
+                continue;
+            }
+            let Some(destination) = positions.get(sell.waypoint_symbol.as_str()) else {
+                continue;
+            };
+
+            let legs = [distance(here, source), distance(source, destination)];
+            if let Some(range) = trader.range {
+                if legs.iter().any(|leg| *leg > range) {
+                    continue;
+                }
+                let [to_source, to_destination] = legs.map(|leg| travel(leg, trader.speed).0);
+                let needed = if fuel_markets.contains(buy.waypoint_symbol.as_str()) {
+                    to_source
+                } else {
+                    to_source + to_destination
+                };
+                if needed > trader.fuel {
+                    continue;
+                }
+            }
+            let (units, gross) = haul(buy, sell, trader.capacity, trader.credits);
+            if units == 0 {
+                continue;
+            }
+
+            let (fuel, seconds) = legs
+                .iter()
+                .map(|leg| travel(*leg, trader.speed))
+                .fold((0, 0), |(f, s), (fuel, seconds)| (f + fuel, s + seconds));
+            let fuel_cost = match trader.range {
+                Some(_) => (fuel * fuel_price).div_ceil(FUEL_PER_UNIT),
+                None => 0,
+            };
+            let profit = gross - fuel_cost as i64;
+            if profit <= min_profit as i64 {
+                continue;
+            }
+
+            let score = profit as f64 / seconds.max(1) as f64;
+            if best.as_ref().is_some_and(|(best, _)| *best >= score) {
+                continue;
+            }
+            best = Some((
+                score,
+                Route {
+                    good: buy.trade_symbol,
+                    buy_at: buy.waypoint_symbol.clone(),
+                    sell_at: sell.waypoint_symbol.clone(),
+                    units,
+                    sell_price: sell.sell_price,
+                    profit,
+                    seconds,
+                },
+            ));
+        }
+    }
+
+    best.map(|(_, route)| route)
+}
+
+/// How many units are worth buying at `buy` and selling at `sell`, and what that earns.
+/// Stops at the first batch that would no longer pay off as prices move.
+fn haul(buy: &PriceRecord, sell: &PriceRecord, capacity: u64, credits: u64) -> (u64, i64) {
+    let batch = buy.trade_volume.min(sell.trade_volume).max(1);
+    let (mut units, mut spent, mut earned) = (0, 0, 0);
+
+    while units < capacity {
+        let units_now = batch.min(capacity - units);
+        let purchase = moved(buy.purchase_price, units / buy.trade_volume.max(1), 1.0);
+        let sale = moved(sell.sell_price, units / sell.trade_volume.max(1), -1.0);
+        if sale <= purchase || spent + purchase * units_now > credits {
+            break;
+        }
+        units += units_now;
+        spent += purchase * units_now;
+        earned += sale * units_now;
+    }
+
+    (units, earned as i64 - spent as i64)
+}
+
+/// `price` after `steps` trade volumes moved it up or down.
+fn moved(price: u64, steps: u64, direction: f64) -> u64 {
+    (price as f64 * (1.0 + direction * PRICE_STEP).powi(steps as i32)).round() as u64
+}
+
+fn distance(a: &(i64, i64), b: &(i64, i64)) -> f64 {
+    (((a.0 - b.0).pow(2) + (a.1 - b.1).pow(2)) as f64).sqrt()
+}
+
+/// The fuel and seconds it takes to cruise `distance`.
+fn travel(distance: f64, speed: u64) -> (u64, u64) {
+    if distance == 0.0 {
+        return (0, 0);
+    }
+    let fuel = distance.round().max(1.0) as u64;
+    let seconds = (15.0 + distance * 25.0 / speed.max(1) as f64).round() as u64;
+    (fuel, seconds)
+}
+
+/// Buys a good where it is cheap and sells it where it is not,
+/// picking the most profitable route within range each time.
+///
+/// Cargo the ship already carries when it has no route is sold first.
+#[derive(Debug)]
+pub struct Trading {
+    config: TradingConfig,
+    route: Option<Route>,
+}
+
+impl Trading {
+    pub fn new(config: TradingConfig) -> Self {
+        Self {
+            config,
+            route: None,
+        }
+    }
+
+    async fn next(&mut self, ctx: &mut ShipContext) -> Result<Step, anyhow::Error> {
+        let ship = ctx.ship().await?;
+        if worn(&ship) {
+            return ctx.repair(&ship).await;
+        }
+
+        let Some(route) = &self.route else {
+            if ship.cargo.units > 0 {
+                return ctx.sell_at_best_market(&ship).await;
+            }
+            self.route = self.find_route(ctx, &ship).await?;
+            return match &self.route {
+                Some(route) => {
+                    event!(
+                        Level::INFO,
+                        "Trading {} units of {:?} from {} to {} for about {} credits",
+                        route.units,
+                        route.good,
+                        route.buy_at,
+                        route.sell_at,
+                        route.profit
+                    );
+                    Ok(Step::Continue)
+                }
+                None => {
+                    event!(Level::INFO, "No profitable trade route in range");
+                    Ok(Step::Wait(NO_ROUTE_WAIT))
+                }
+            };
+        };
+
+        let carrying = ship
+            .cargo
+            .inventory
+            .iter()
+            .any(|item| item.symbol == route.good);
+        let destination = if carrying {
+            &route.sell_at
+        } else {
+            &route.buy_at
+        };
+        if &ship.nav.waypoint_symbol != destination {
+            ctx.navigate(&ship, destination).await?;
+            return Ok(Step::Continue);
+        }
+        ctx.dock(&ship).await?;
+        let market = ctx.market(&ship).await?;
+        ctx.refuel(&ship, &market).await?;
+
+        if carrying {
+            let ship = ctx.ship().await?;
+            let earned = ctx.sell_cargo(&ship, &market).await?;
+            event!(
+                Level::INFO,
+                "Sold {:?} at {} for {earned} credits",
+                route.good,
+                route.sell_at
+            );
+            self.route = None;
+        } else if ctx
+            .purchase(&market, route.good, route.units, route.sell_price, 0)
+            .await?
+            == 0
+        {
+            event!(
+                Level::INFO,
+                "{:?} is no longer worth buying at {}",
+                route.good,
+                route.buy_at
+            );
+            self.route = None;
+        }
+
+        Ok(Step::Continue)
+    }
+
+    async fn find_route(
+        &self,
+        ctx: &ShipContext,
+        ship: &Ship,
+    ) -> Result<Option<Route>, anyhow::Error> {
+        let system = &ship.nav.system_symbol;
+        let prices = ctx.store().latest_prices(system)?;
+        let waypoints = ctx.store().waypoints_in_system(system)?;
+        let positions = waypoints
+            .iter()
+            .map(|w| (w.value.symbol.as_str(), (w.value.x, w.value.y)))
+            .collect();
+        let fuel_price = prices
+            .iter()
+            .filter(|record| record.trade_symbol == TradeSymbol::Fuel)
+            .map(|record| record.purchase_price)
+            .min()
+            .unwrap_or_default();
+        let credits = ctx.client().await.get_agent().await?.credits;
+
+        let range = match (self.config.range, ship.fuel.capacity) {
+            (_, 0) => None,
+            (Some(range), _) => Some(range as f64),
+            (None, capacity) => Some(capacity as f64),
+        };
+        let trader = Trader {
+            waypoint: &ship.nav.waypoint_symbol,
+            speed: ship.engine.speed,
+            capacity: ship.cargo.capacity - ship.cargo.units,
+            range,
+            fuel: ship.fuel.current,
+            credits: credits.max(0) as u64,
+        };
+
+        Ok(plan(
+            &prices,
+            &positions,
+            &trader,
+            fuel_price,
+            self.config.min_profit,
+        ))
+    }
+}
+
+impl Behavior for Trading {
+    fn step<'a>(&'a mut self, ship: &'a mut ShipContext) -> StepFuture<'a> {
+        Box::pin(self.next(ship))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{SupplyLevel, TradeGoodType};
+
+    fn price(waypoint: &str, good: TradeSymbol, purchase: u64, sell: u64) -> PriceRecord {
+        PriceRecord {
+            waypoint_symbol: waypoint.to_string(),
+            trade_symbol: good,
+            recorded_at: 0,
+            good_type: TradeGoodType::Exchange,
+            supply: SupplyLevel::Moderate,
+            activity: None,
+            purchase_price: purchase,
+            sell_price: sell,
+            trade_volume: 10,
+        }
+    }
+
+    fn trader(credits: u64) -> Trader<'static> {
+        Trader {
+            waypoint: "X1-A-1",
+            speed: 10,
+            capacity: 40,
+            range: Some(100.0),
+            fuel: 100,
+            credits,
+        }
+    }
+
+    #[test]
+    fn picks_the_route_earning_the_most_per_second() {
+        let prices = [
+            price("X1-A-1", TradeSymbol::IronOre, 10, 8),
+            price("X1-A-2", TradeSymbol::IronOre, 30, 25),
+            price("X1-A-1", TradeSymbol::CopperOre, 10, 8),
+            price("X1-A-3", TradeSymbol::CopperOre, 40, 35),
+            price("X1-A-4", TradeSymbol::Gold, 10, 8),
+            price("X1-A-5", TradeSymbol::Gold, 500, 400),
+        ];
+        let positions = HashMap::from([
+            ("X1-A-1", (0, 0)),
+            ("X1-A-2", (10, 0)),
+            ("X1-A-3", (90, 0)),
+            ("X1-A-4", (0, 500)),
+            ("X1-A-5", (0, 510)),
+        ]);
+
+        let route = plan(&prices, &positions, &trader(10_000), 0, 0).unwrap();
+
+        assert_eq!(route.good, TradeSymbol::IronOre);
+        assert_eq!(route.sell_at, "X1-A-2");
+        assert_eq!(route.units, 40);
+    }
+
+    #[test]
+    fn stops_buying_when_prices_move_too_far() {
+        let buy = price("X1-A-1", TradeSymbol::IronOre, 100, 90);
+        let sell = price("X1-A-2", TradeSymbol::IronOre, 120, 112);
+
+        assert_eq!(haul(&buy, &sell, 100, 1_000_000).0, 20);
+        assert_eq!(haul(&buy, &sell, 100, 1_500).0, 10);
+    }
+
+    #[test]
+    fn fuel_can_make_a_route_unprofitable() {
+        let prices = [
+            price("X1-A-1", TradeSymbol::IronOre, 10, 8),
+            price("X1-A-2", TradeSymbol::IronOre, 12, 11),
+        ];
+        let positions = HashMap::from([("X1-A-1", (0, 0)), ("X1-A-2", (90, 0))]);
+
+        assert!(plan(&prices, &positions, &trader(10_000), 0, 0).is_some());
+        assert!(plan(&prices, &positions, &trader(10_000), 100, 0).is_none());
+    }
+
+    #[test]
+    fn both_legs_have_to_fit_the_tank_unless_fuel_is_sold_on_the_way() {
+        let mut prices = vec![
+            price("X1-A-2", TradeSymbol::IronOre, 10, 8),
+            price("X1-A-3", TradeSymbol::IronOre, 30, 25),
+        ];
+        let positions =
+            HashMap::from([("X1-A-1", (0, 0)), ("X1-A-2", (40, 0)), ("X1-A-3", (80, 0))]);
+        let mut trader = trader(10_000);
+        trader.fuel = 60;
+
+        assert!(plan(&prices, &positions, &trader, 0, 0).is_none());
+
+        prices.push(price("X1-A-2", TradeSymbol::Fuel, 1, 1));
+        assert!(plan(&prices, &positions, &trader, 0, 0).is_some());
+
+        trader.fuel = 30;
+        assert!(plan(&prices, &positions, &trader, 0, 0).is_none());
+    }
+}
@@ -0,0 +1,85 @@
+use std::{sync::Arc, time::Duration};
+
+use tokio::{
+    sync::Mutex,
+    time::{interval, MissedTickBehavior},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{event, instrument, Level};
+
+use crate::{client::Client, config::HistoryConfig, model::Agent, store::Store};
+
+/// The largest page size the API allows.
+const PAGE_SIZE: u64 = 20;
+
+/// Sample the game stats, the leaderboards and our agent now and then,
+/// so the dashboard can chart how we do over the course of a reset.
+#[instrument(name = "history", level = Level::INFO, skip_all)]
+pub async fn run(
+    config: HistoryConfig,
+    client: Arc<Mutex<Client>>,
+    store: Arc<Store>,
+    shutdown: CancellationToken,
+) -> Result<(), anyhow::Error> {
+    let mut ticker = interval(Duration::from_secs(config.interval_secs.max(1)));
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => return Ok(()),
+            _ = ticker.tick() => {}
+        }
+
+        let (status, agent) = {
+            let mut client = client.lock().await;
+            let status = match client.get_status().await {
+                Ok(status) => status,
+                Err(err) => {
+                    event!(Level::WARN, "Failed to fetch status: {err:?}");
+                    continue;
+                }
+            };
+            // The leaderboards are still worth keeping without our agent.
+            let agent = client
+                .get_agent()
+                .await
+                .inspect_err(|err| event!(Level::WARN, "Failed to fetch agent: {err:?}"))
+                .ok();
+            (status, agent)
+        };
+
+        // The leaderboard only shows the top agents, so our place is counted among all of them.
+        let credits_rank = match &agent {
+            Some(agent) => credits_rank(&client, agent)
+                .await
+                .inspect_err(|err| event!(Level::WARN, "Failed to rank agent: {err:?}"))
+                .ok(),
+            None => None,
+        };
+
+        let recorded_at = chrono::Utc::now().timestamp_millis();
+        store.record_history(recorded_at, &status, agent.as_ref(), credits_rank)?;
+    }
+}
+
+/// Our place among all agents by credits, starting at 1.
+/// The client is only locked for one page at a time, since there can be many agents.
+async fn credits_rank(client: &Mutex<Client>, agent: &Agent) -> Result<u32, anyhow::Error> {
+    let mut ahead = 0;
+    for page in 1.. {
+        let (agents, meta) = client
+            .lock()
+            .await
+            .list_agents(Some(PAGE_SIZE), Some(page))
+            .await?;
+        ahead += agents
+            .iter()
+            .filter(|other| other.symbol != agent.symbol && other.credits > agent.credits)
+            .count() as u32;
+        if agents.is_empty() || page * PAGE_SIZE >= meta.total {
+            break;
+        }
+    }
+
+    Ok(ahead + 1)
+}
@@ -0,0 +1,140 @@
+use std::{
+    fmt,
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::Context;
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use tracing::{event, instrument, Level};
+
+use crate::{client, model::System, store::Store};
+
+/// How many systems are written to the store in one transaction.
+const BATCH_SIZE: usize = 500;
+
+/// Import a `systems.json` snapshot of the universe into the store.
+///
+/// `source` is either an HTTPS URL, which is downloaded to a temporary file
+/// first, or a local path. The file is parsed as a stream, so the whole
+/// universe is never held in memory at once. Returns the number of systems.
+#[instrument(level = Level::INFO, skip(store))]
+pub async fn import_systems(source: &str, store: Arc<Store>) -> Result<u64, anyhow::Error> {
+    let (path, downloaded) = if source.starts_with("https://") {
+        let path =
+            std::env::temp_dir().join(format!("catfleet-systems-{}.json", std::process::id()));
+        let bytes = client::download(source, &path)
+            .await
+            .with_context(|| format!("Downloading `{source}`"))?;
+        event!(Level::INFO, "Downloaded {bytes} bytes");
+        (path, true)
+    } else {
+        (PathBuf::from(source), false)
+    };
+
+    let res = {
+        let path = path.clone();
+        tokio::task::spawn_blocking(move || read_systems(&path, &store)).await?
+    };
+
+    if downloaded {
+        let _ = tokio::fs::remove_file(&path).await;
+    }
+
+    let count = res.with_context(|| format!("Importing `{source}`"))?;
+    event!(Level::INFO, "Imported {count} systems");
+    Ok(count)
+}
+
+fn read_systems(path: &Path, store: &Store) -> Result<u64, anyhow::Error> {
+    let file = File::open(path).with_context(|| format!("Opening `{}`", path.display()))?;
+    let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(file));
+
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+    let count = deserializer.deserialize_seq(EachSystem(|system| {
+        batch.push(system);
+        if batch.len() == BATCH_SIZE {
+            store.put_all(&batch)?;
+            batch.clear();
+        }
+        Ok(())
+    }))?;
+    deserializer.end()?;
+    store.put_all(&batch)?;
+
+    Ok(count)
+}
+
+/// Passes each system of a JSON array to a callback as soon as it is parsed.
+struct EachSystem<F>(F);
+
+impl<'de, F> Visitor<'de> for EachSystem<F>
+where
+    F: FnMut(System) -> Result<(), anyhow::Error>,
+{
+    type Value = u64;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array of systems")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(mut self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut count = 0;
+        while let Some(system) = seq.next_element::<System>()? {
+            (self.0)(system).map_err(de::Error::custom)?;
+            count += 1;
+        }
+
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn imports_systems_from_a_file() {
+        let path =
+            std::env::temp_dir().join(format!("catfleet-test-systems-{}.json", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"[
+                {
+                    "symbol": "X1-A",
+                    "sectorSymbol": "X1",
+                    "type": "RED_STAR",
+                    "x": 1,
+                    "y": 2,
+                    "waypoints": [
+                        { "symbol": "X1-A-1", "type": "PLANET", "x": 3, "y": 4, "orbitals": [{ "symbol": "X1-A-2" }] },
+                        { "symbol": "X1-A-2", "type": "MOON", "x": 3, "y": 4, "orbitals": [], "orbits": "X1-A-1" }
+                    ],
+                    "factions": [{ "symbol": "COSMIC" }]
+                },
+                {
+                    "symbol": "X1-B",
+                    "sectorSymbol": "X1",
+                    "type": "BLUE_STAR",
+                    "x": 5,
+                    "y": 6,
+                    "waypoints": [],
+                    "factions": []
+                }
+            ]"#,
+        )
+        .unwrap();
+        let store = Arc::new(Store::open_in_memory().unwrap());
+
+        let count = import_systems(path.to_str().unwrap(), store.clone()).await;
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(count.unwrap(), 2);
+        assert_eq!(store.count::<System>().unwrap(), 2);
+        let system = store.get::<System>("X1-A").unwrap().unwrap().value;
+        assert_eq!(system.waypoints.len(), 2);
+        assert_eq!(system.waypoints[1].orbits.as_deref(), Some("X1-A-1"));
+    }
+}
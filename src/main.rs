@@ -1,11 +1,37 @@
+use std::{path::Path, process::ExitCode, sync::Arc};
+
+use tokio::sync::Mutex;
+use tracing::{event, Level};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
+use cache::Cache;
+use client::Client;
+use config::Config;
+use events::Events;
+use fleet::Fleet;
+use store::Store;
+use supervisor::{Exit, Supervisor};
+
+mod cache;
 mod client;
+mod config;
+mod contracts;
+mod crawl;
+mod events;
+mod expansion;
+mod fleet;
+mod history;
+mod import;
+mod metrics;
 mod model;
+mod reset;
 mod server;
+mod snapshot;
+mod store;
+mod supervisor;
 
 #[tokio::main]
-async fn main() {
+async fn main() -> ExitCode {
     tracing_subscriber::registry()
         .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| {
             if cfg!(debug_assertions) {
@@ -17,5 +43,176 @@ async fn main() {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
-    tokio::spawn(server::start()).await.unwrap()
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(err) => {
+            event!(Level::ERROR, "Failed to load config: {err:?}");
+            return Exit::Config.into();
+        }
+    };
+
+    let store = match Store::open(&config.store.path) {
+        Ok(store) => Arc::new(store),
+        Err(err) => {
+            event!(Level::ERROR, "Failed to open store: {err:?}");
+            return Exit::Store.into();
+        }
+    };
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let command = match args.as_slice() {
+        [] => None,
+        ["snapshot", "export", path] => Some(snapshot::export(&store, Path::new(path))),
+        ["snapshot", "import", path, database] => Some(snapshot::import(
+            Path::new(path),
+            Path::new(database),
+            &config.store.path,
+        )),
+        _ => {
+            event!(
+                Level::ERROR,
+                "Usage: catfleet [snapshot export <path> | snapshot import <path> <database>]"
+            );
+            return Exit::Usage.into();
+        }
+    };
+    if let Some(res) = command {
+        return match res.and_then(|()| store.checkpoint()) {
+            Ok(()) => Exit::Clean.into(),
+            Err(err) => {
+                event!(Level::ERROR, "Command failed: {err:?}");
+                Exit::TaskFailed.into()
+            }
+        };
+    }
+
+    let cache = Arc::new(Cache::new(store.clone()));
+    let events = Events::new();
+
+    let client = match Client::new().await {
+        Ok(mut client) => {
+            client.add_observer(store.clone());
+            client.add_observer(cache.clone());
+            Arc::new(Mutex::new(client))
+        }
+        Err(err) => {
+            event!(
+                Level::ERROR,
+                "Failed to connect to the SpaceTraders API: {err:?}"
+            );
+            return Exit::Upstream.into();
+        }
+    };
+
+    // Prefer the token of the agent we registered ourselves after the last reset.
+    match store.agent_token() {
+        Ok(Some(token)) => {
+            if let Err(err) = client.lock().await.set_token(Some(&token)) {
+                event!(Level::WARN, "Failed to use the stored token: {err:?}");
+            }
+        }
+        Ok(None) => {}
+        Err(err) => event!(Level::WARN, "Failed to read the stored token: {err:?}"),
+    }
+
+    let fleet = match Fleet::new(store.clone()) {
+        Ok(fleet) => Arc::new(fleet),
+        Err(err) => {
+            event!(Level::ERROR, "Failed to load the fleet state: {err:?}");
+            return Exit::Store.into();
+        }
+    };
+
+    let supervisor = Supervisor::new();
+    supervisor.spawn(
+        "server",
+        server::start(
+            config.server,
+            client.clone(),
+            store.clone(),
+            cache.clone(),
+            events.clone(),
+            fleet.clone(),
+            supervisor.token(),
+        ),
+    );
+    if config.fleet.enabled {
+        supervisor.spawn(
+            "fleet",
+            fleet::run(
+                fleet,
+                config.fleet,
+                client.clone(),
+                cache.clone(),
+                supervisor.token(),
+            ),
+        );
+    }
+    if config.reset.enabled {
+        supervisor.spawn(
+            "reset",
+            reset::watch(
+                config.reset,
+                config.agent,
+                client.clone(),
+                store.clone(),
+                cache,
+                events.clone(),
+                supervisor.token(),
+            ),
+        );
+    }
+    if config.contracts.enabled {
+        supervisor.spawn(
+            "contracts",
+            contracts::run(
+                config.contracts,
+                client.clone(),
+                store.clone(),
+                events.clone(),
+                supervisor.token(),
+            ),
+        );
+    }
+    if config.history.enabled {
+        supervisor.spawn(
+            "history",
+            history::run(
+                config.history,
+                client.clone(),
+                store.clone(),
+                supervisor.token(),
+            ),
+        );
+    }
+    if config.expansion.enabled {
+        supervisor.spawn(
+            "expansion",
+            expansion::run(
+                config.expansion,
+                client.clone(),
+                store.clone(),
+                supervisor.token(),
+            ),
+        );
+    }
+    if config.crawl.enabled {
+        supervisor.spawn(
+            "crawl",
+            crawl::run(
+                config.crawl,
+                client,
+                store.clone(),
+                events,
+                supervisor.token(),
+            ),
+        );
+    }
+
+    let exit = supervisor.run().await;
+    if let Err(err) = store.checkpoint() {
+        event!(Level::WARN, "Failed to checkpoint store: {err:?}");
+    }
+    exit.into()
 }
@@ -0,0 +1,320 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{
+        atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use hyper::{Method, StatusCode};
+
+use crate::{
+    client::Observer,
+    model::{ApiResponseData, ShipNavStatus},
+};
+
+/// Metrics about catfleet's use of the SpaceTraders API and the state of the fleet,
+/// rendered in the Prometheus text exposition format.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    /// Requests sent upstream, keyed by method and endpoint.
+    requests: Mutex<BTreeMap<(String, String), u64>>,
+    /// Failed upstream requests, keyed by status code,
+    /// or `transport` if no response was received at all.
+    upstream_errors: Mutex<BTreeMap<String, u64>>,
+    rate_limit_remaining: Arc<AtomicU64>,
+    rate_limit_wait_micros: AtomicU64,
+    /// Whether the last upstream request received a response.
+    upstream_reachable: AtomicBool,
+    /// Whether the last authenticated request was accepted.
+    token_valid: AtomicBool,
+    credits: AtomicI64,
+    ship_count: AtomicU64,
+    ships: Mutex<BTreeMap<String, ShipNavStatus>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The gauge the rate limiter publishes its remaining requests to.
+    pub fn rate_limit_remaining(&self) -> Arc<AtomicU64> {
+        self.rate_limit_remaining.clone()
+    }
+
+    pub fn record_request(&self, method: &Method, path: &str) {
+        let key = (method.to_string(), endpoint(path));
+        *self.requests.lock().unwrap().entry(key).or_default() += 1;
+    }
+
+    pub fn record_response(&self, path: &str, status: StatusCode) {
+        self.upstream_reachable.store(true, Ordering::Relaxed);
+
+        if status == StatusCode::UNAUTHORIZED {
+            self.token_valid.store(false, Ordering::Relaxed);
+        } else if status.is_success() && path.starts_with("/my/") {
+            self.token_valid.store(true, Ordering::Relaxed);
+        }
+
+        if status.is_client_error() || status.is_server_error() {
+            self.record_upstream_error(status.as_str());
+        }
+    }
+
+    pub fn record_transport_error(&self) {
+        self.upstream_reachable.store(false, Ordering::Relaxed);
+        self.record_upstream_error("transport");
+    }
+
+    fn record_upstream_error(&self, kind: &str) {
+        *self
+            .upstream_errors
+            .lock()
+            .unwrap()
+            .entry(kind.to_string())
+            .or_default() += 1;
+    }
+
+    pub fn record_rate_limit_wait(&self, wait: Duration) {
+        self.rate_limit_wait_micros
+            .fetch_add(wait.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    pub fn upstream_reachable(&self) -> bool {
+        self.upstream_reachable.load(Ordering::Relaxed)
+    }
+
+    pub fn token_valid(&self) -> bool {
+        self.token_valid.load(Ordering::Relaxed)
+    }
+
+    /// Render all metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        header(
+            &mut out,
+            "catfleet_upstream_requests_total",
+            "counter",
+            "Requests sent to the SpaceTraders API.",
+        );
+        for ((method, endpoint), count) in self.requests.lock().unwrap().iter() {
+            let _ = writeln!(
+                out,
+                "catfleet_upstream_requests_total{{method=\"{}\",endpoint=\"{}\"}} {count}",
+                escape(method),
+                escape(endpoint)
+            );
+        }
+
+        header(
+            &mut out,
+            "catfleet_upstream_errors_total",
+            "counter",
+            "Failed requests to the SpaceTraders API by status code.",
+        );
+        for (kind, count) in self.upstream_errors.lock().unwrap().iter() {
+            let _ = writeln!(
+                out,
+                "catfleet_upstream_errors_total{{status=\"{}\"}} {count}",
+                escape(kind)
+            );
+        }
+
+        header(
+            &mut out,
+            "catfleet_rate_limit_remaining",
+            "gauge",
+            "Requests remaining in the rate limit buckets.",
+        );
+        let _ = writeln!(
+            out,
+            "catfleet_rate_limit_remaining {}",
+            self.rate_limit_remaining.load(Ordering::Relaxed)
+        );
+
+        header(
+            &mut out,
+            "catfleet_rate_limit_wait_seconds_total",
+            "counter",
+            "Time spent waiting for the rate limiter.",
+        );
+        let _ = writeln!(
+            out,
+            "catfleet_rate_limit_wait_seconds_total {}",
+            self.rate_limit_wait_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0
+        );
+
+        header(
+            &mut out,
+            "catfleet_agent_credits",
+            "gauge",
+            "Credits of the agent.",
+        );
+        let _ = writeln!(
+            out,
+            "catfleet_agent_credits {}",
+            self.credits.load(Ordering::Relaxed)
+        );
+
+        header(
+            &mut out,
+            "catfleet_agent_ships",
+            "gauge",
+            "Number of ships owned by the agent.",
+        );
+        let _ = writeln!(
+            out,
+            "catfleet_agent_ships {}",
+            self.ship_count.load(Ordering::Relaxed)
+        );
+
+        header(
+            &mut out,
+            "catfleet_ship_status",
+            "gauge",
+            "Navigation status of each known ship.",
+        );
+        for (ship, status) in self.ships.lock().unwrap().iter() {
+            let _ = writeln!(
+                out,
+                "catfleet_ship_status{{ship=\"{}\",status=\"{status}\"}} 1",
+                escape(ship)
+            );
+        }
+
+        out
+    }
+}
+
+impl Observer for Metrics {
+    fn observe(&self, ship: Option<&str>, data: &ApiResponseData) {
+        let agent = match data {
+            ApiResponseData::GetAgent(agent)
+            | ApiResponseData::ShipPurchase { agent, .. }
+            | ApiResponseData::MarketTransaction { agent, .. }
+            | ApiResponseData::RefuelShip { agent, .. }
+            | ApiResponseData::JumpShip { agent, .. }
+            | ApiResponseData::ModifyMount { agent, .. }
+            | ApiResponseData::ScrapShip { agent, .. }
+            | ApiResponseData::RepairShip { agent, .. }
+            | ApiResponseData::UpdateContract {
+                agent: Some(agent), ..
+            } => Some(agent),
+            _ => None,
+        };
+        // The account ID is only included on our own agent.
+        if let Some(agent) = agent.filter(|agent| agent.account_id.is_some()) {
+            self.credits.store(agent.credits, Ordering::Relaxed);
+            self.ship_count.store(agent.ship_count, Ordering::Relaxed);
+        }
+
+        let mut ships = self.ships.lock().unwrap();
+        match data {
+            ApiResponseData::ListShips(list) => {
+                for ship in list {
+                    ships.insert(ship.symbol.clone(), ship.nav.status);
+                }
+            }
+            ApiResponseData::GetShip(ship)
+            | ApiResponseData::ShipPurchase { ship, .. }
+            | ApiResponseData::RepairShip { ship, .. } => {
+                ships.insert(ship.symbol.clone(), ship.nav.status);
+            }
+            ApiResponseData::ScrapShip { .. } => {
+                if let Some(ship) = ship {
+                    ships.remove(ship);
+                }
+            }
+            ApiResponseData::GetNav(nav)
+            | ApiResponseData::NavigateShip { nav, .. }
+            | ApiResponseData::WarpShip { nav, .. } => {
+                if let Some(ship) = ship {
+                    ships.insert(ship.to_string(), nav.status);
+                }
+            }
+            ApiResponseData::JumpShip { nav, .. } => {
+                if let Some(ship) = ship {
+                    ships.insert(ship.to_string(), nav.status);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Collapse the symbols and IDs in a request path, so that all requests
+/// to the same endpoint are counted together.
+///
+/// Path segments of the API itself are lowercase words, whereas symbols
+/// are uppercase and IDs contain digits.
+fn endpoint(path: &str) -> String {
+    if path.is_empty() || path == "/" {
+        return "/".to_string();
+    }
+
+    path.split('/')
+        .map(|segment| {
+            if segment
+                .chars()
+                .any(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+            {
+                "{id}"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collapses_symbols_in_endpoints() {
+        assert_eq!(endpoint(""), "/");
+        assert_eq!(endpoint("/my/agent"), "/my/agent");
+        assert_eq!(endpoint("/my/ships/CATBRAINED-1/nav"), "/my/ships/{id}/nav");
+        assert_eq!(
+            endpoint("/systems/X1-AB12/waypoints/X1-AB12-C3/market"),
+            "/systems/{id}/waypoints/{id}/market"
+        );
+        assert_eq!(
+            endpoint("/my/contracts/cm5ggu4qk0eavs60cu0ap3ld8/accept"),
+            "/my/contracts/{id}/accept"
+        );
+    }
+
+    #[test]
+    fn renders_prometheus_text() {
+        let metrics = Metrics::new();
+        metrics.record_request(&Method::GET, "/my/ships/CATBRAINED-1");
+        metrics.record_request(&Method::GET, "/my/ships/CATBRAINED-2");
+        metrics.record_response("/my/ships/CATBRAINED-2", StatusCode::TOO_MANY_REQUESTS);
+
+        let text = metrics.render();
+
+        assert!(text.contains(
+            "catfleet_upstream_requests_total{method=\"GET\",endpoint=\"/my/ships/{id}\"} 2\n"
+        ));
+        assert!(text.contains("catfleet_upstream_errors_total{status=\"429\"} 1\n"));
+        assert!(text.contains("# TYPE catfleet_rate_limit_remaining gauge\n"));
+    }
+}
@@ -0,0 +1,114 @@
+//! Objects for tests that don't care about most of their fields.
+
+use serde_json::json;
+
+use super::{Contract, Ship, Waypoint};
+
+/// A docked mining drone with an empty cargo hold of 15 units and full fuel tanks.
+pub fn ship(symbol: &str, waypoint: &str) -> Ship {
+    let system = waypoint
+        .rsplit_once('-')
+        .map_or(waypoint, |(system, _)| system);
+    let requirements = json!({ "power": 1, "crew": 0 });
+    let route_waypoint = json!({
+        "symbol": waypoint,
+        "type": "ASTEROID",
+        "systemSymbol": system,
+        "x": 0,
+        "y": 0
+    });
+
+    serde_json::from_value(json!({
+        "symbol": symbol,
+        "registration": { "name": symbol, "factionSymbol": "COSMIC", "role": "EXCAVATOR" },
+        "nav": {
+            "systemSymbol": system,
+            "waypointSymbol": waypoint,
+            "route": {
+                "destination": route_waypoint,
+                "origin": route_waypoint,
+                "departureTime": "2025-01-01T00:00:00.000Z",
+                "arrival": "2025-01-01T00:00:00.000Z"
+            },
+            "status": "DOCKED",
+            "flightMode": "CRUISE"
+        },
+        "crew": { "current": 0, "required": 0, "capacity": 0, "rotation": "STRICT", "morale": 100, "wages": 0 },
+        "frame": {
+            "symbol": "FRAME_DRONE",
+            "name": "Drone",
+            "description": "",
+            "condition": 1.0,
+            "integrity": 1.0,
+            "moduleSlots": 2,
+            "mountingPoints": 2,
+            "fuelCapacity": 80,
+            "requirements": requirements
+        },
+        "reactor": {
+            "symbol": "REACTOR_SOLAR_I",
+            "name": "Solar Reactor",
+            "description": "",
+            "condition": 1.0,
+            "integrity": 1.0,
+            "powerOutput": 3,
+            "requirements": requirements
+        },
+        "engine": {
+            "symbol": "ENGINE_IMPULSE_DRIVE_I",
+            "name": "Impulse Drive",
+            "description": "",
+            "condition": 1.0,
+            "integrity": 1.0,
+            "speed": 9,
+            "requirements": requirements
+        },
+        "cooldown": { "shipSymbol": symbol, "totalSeconds": 0, "remainingSeconds": 0 },
+        "modules": [],
+        "mounts": [],
+        "cargo": { "capacity": 15, "units": 0, "inventory": [] },
+        "fuel": { "current": 80, "capacity": 80 }
+    }))
+    .unwrap()
+}
+
+/// An accepted procurement contract for `units` of iron ore, delivered to `destination`,
+/// paying 1000 credits up front and 10000 on fulfillment.
+pub fn contract(id: &str, destination: &str, units: u64, deadline: &str) -> Contract {
+    serde_json::from_value(json!({
+        "id": id,
+        "factionSymbol": "COSMIC",
+        "type": "PROCUREMENT",
+        "terms": {
+            "deadline": deadline,
+            "payment": { "onAccepted": 1000, "onFulfilled": 10000 },
+            "deliver": [{
+                "tradeSymbol": "IRON_ORE",
+                "destinationSymbol": destination,
+                "unitsRequired": units,
+                "unitsFulfilled": 0
+            }]
+        },
+        "accepted": true,
+        "fulfilled": false,
+        "expiration": deadline,
+        "deadlineToAccept": deadline
+    }))
+    .unwrap()
+}
+
+/// An uncharted planet without traits at `x`, `y`.
+pub fn waypoint(symbol: &str, x: i64, y: i64) -> Waypoint {
+    let system = symbol.rsplit_once('-').map_or(symbol, |(system, _)| system);
+    serde_json::from_value(json!({
+        "symbol": symbol,
+        "type": "PLANET",
+        "systemSymbol": system,
+        "x": x,
+        "y": y,
+        "orbitals": [],
+        "traits": [],
+        "isUnderConstruction": false
+    }))
+    .unwrap()
+}
@@ -3,12 +3,15 @@ use std::fmt::Display;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+#[cfg(test)]
+pub mod fixtures;
+
 /// The activity level of a trade good.
 /// If the good is an import, this represents how strong consumption is.
 /// If the good is an export, this represents how strong the production is for the good.
 /// When activity is strong, consumption or production is near maximum capacity.
 /// When activity is weak, consumption or production is near minimum capacity.
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ActivityLevel {
     Weak,
@@ -18,7 +21,7 @@ pub enum ActivityLevel {
 }
 
 /// Agent details.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename = "agent", rename_all = "camelCase")]
 pub struct Agent {
     /// Account ID that is tied to this agent. Only included on your own agent.
@@ -42,7 +45,7 @@ pub struct Agent {
 
 /// The chart of a system or waypoint, which makes the
 /// location visible to other agents.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename = "chart", rename_all = "camelCase")]
 pub struct Chart {
     /// The symbol of the waypoint.
@@ -55,7 +58,7 @@ pub struct Chart {
 }
 
 // TODO: Figure out where this is used.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename = "connectedSystem", rename_all = "camelCase")]
 struct ConnectedSystem {
     /// The symbol of the system.
@@ -78,7 +81,7 @@ struct ConnectedSystem {
 }
 
 /// The type of system.
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum SystemType {
     NeutronStar,
@@ -94,7 +97,7 @@ pub enum SystemType {
 }
 
 /// The construction details of a waypoint.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename = "construction", rename_all = "camelCase")]
 pub struct Construction {
     /// The symbol of the waypoint.
@@ -107,7 +110,7 @@ pub struct Construction {
 
 /// The details of the required construction materials
 /// for a given waypoint under construction.
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ConstructionMaterial {
     /// The good's symbol.
@@ -118,7 +121,7 @@ pub struct ConstructionMaterial {
     pub fulfilled: u64,
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum TradeSymbol {
     PreciousStones,
@@ -267,7 +270,7 @@ pub enum TradeSymbol {
 }
 
 /// Contract details.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename = "contract", rename_all = "camelCase")]
 pub struct Contract {
     /// ID of the contract.
@@ -291,7 +294,7 @@ pub struct Contract {
     pub deadline_to_accept: String, // TODO: This is supposed to be a "date-time". Figure out the correct Rust type for that.
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ContractType {
     Procurement,
@@ -300,7 +303,7 @@ pub enum ContractType {
 }
 
 /// The terms to fulfill the contract.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ContractTerms {
     /// The deadline for the contract.
@@ -312,7 +315,7 @@ pub struct ContractTerms {
 }
 
 /// Payments for the contract.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ContractPayment {
     /// The amount of credits received up front for accepting the contract.
@@ -323,7 +326,7 @@ pub struct ContractPayment {
 
 /// The details of a delivery contract.
 /// Includes the type of good, units needed, and the destination.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ContractDeliverGood {
     /// The symbol of the trade good to deliver.
@@ -339,7 +342,7 @@ pub struct ContractDeliverGood {
 }
 
 /// A cooldown is a period of time in which a ship cannot perform certain actions.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Cooldown {
     /// The symbol of the ship that is on cooldown.
@@ -356,7 +359,7 @@ pub struct Cooldown {
 }
 
 /// Extraction details.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Extraction {
     /// Symbol of the ship that executed the extraction.
@@ -368,7 +371,7 @@ pub struct Extraction {
 }
 
 /// A yield from the extraction operation.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ExtractionYield {
     /// The good's symbol.
@@ -378,7 +381,7 @@ pub struct ExtractionYield {
 }
 
 /// Faction details.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Faction {
     /// The symbol of the faction.
@@ -399,7 +402,7 @@ pub struct Faction {
 }
 
 /// The symbol of the faction.
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum FactionSymbol {
     Cosmic,
@@ -451,7 +454,7 @@ impl Display for FactionSymbol {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct FactionTrait {
     /// The unique identifier of the trait.
@@ -462,7 +465,7 @@ pub struct FactionTrait {
     pub description: String,
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum FactionTraitSymbol {
     Bureaucratic,
@@ -526,7 +529,7 @@ pub enum FactionTraitSymbol {
     Entrepreneurial,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct JumpGate {
     /// The symbol of the waypoint.
@@ -536,7 +539,7 @@ pub struct JumpGate {
     pub connections: Vec<String>, // XXX: Are these the ConnectedSystem things?
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Market {
     /// The symbol of the market. The symbol is the same
@@ -556,7 +559,7 @@ pub struct Market {
     pub trade_goods: Option<Vec<MarketTradeGood>>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TradeGood {
     /// The good's symbol.
@@ -567,7 +570,7 @@ pub struct TradeGood {
     pub description: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct MarketTradeGood {
     /// The good's symbol.
@@ -599,7 +602,7 @@ pub struct MarketTradeGood {
     pub sell_price: u64,
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum TradeGoodType {
     Export,
@@ -607,7 +610,7 @@ pub enum TradeGoodType {
     Exchange,
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum SupplyLevel {
     Scarce,
@@ -618,7 +621,7 @@ pub enum SupplyLevel {
 }
 
 /// Result of a transaction with a market.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct MarketTransaction {
     /// The symbol of the waypoint.
@@ -644,7 +647,7 @@ pub struct MarketTransaction {
     pub timestamp: String, // TODO: This is supposed to be a "date-time". Figure out the correct Rust type for that.
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum TransactionType {
     Purchase,
@@ -652,7 +655,7 @@ pub enum TransactionType {
 }
 
 /// Meta details for pagination.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Meta {
     /// Show the total amount of items of this kind that exist.
@@ -669,7 +672,7 @@ pub struct Meta {
 }
 
 /// Result of a repair or scrap transaction (or preview thereof).
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipTransaction {
     /// The symbol of the waypoint.
@@ -687,7 +690,7 @@ pub struct ShipTransaction {
 /// The ship that was scanned.
 /// Details include information about the ship that could be
 /// detected by the scanner.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ScannedShip {
     /// The globally unique identifier of the ship.
@@ -707,7 +710,7 @@ pub struct ScannedShip {
 }
 
 /// Details of a system that was scanned.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ScannedSystem {
     /// Symbol of the system.
@@ -728,7 +731,7 @@ pub struct ScannedSystem {
 }
 
 /// A waypoint that was scanned by a ship.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ScannedWaypoint {
     /// The symbol of the waypoint.
@@ -756,7 +759,7 @@ pub struct ScannedWaypoint {
 }
 
 /// Ship details.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Ship {
     /// The globally unique identifier of the ship
@@ -796,8 +799,58 @@ pub struct Ship {
     pub fuel: ShipFuel,
 }
 
+impl Ship {
+    /// Apply the partial update contained in a response about this ship,
+    /// like the new `ShipNav` after navigating.
+    pub fn apply(&mut self, data: &ApiResponseData) {
+        match data {
+            ApiResponseData::GetNav(nav) => self.nav = nav.clone(),
+            ApiResponseData::NavigateShip { fuel, nav, .. }
+            | ApiResponseData::WarpShip { fuel, nav } => {
+                self.nav = nav.clone();
+                self.fuel = fuel.clone();
+            }
+            ApiResponseData::JumpShip { nav, cooldown, .. } => {
+                self.nav = nav.as_ref().clone();
+                self.cooldown = cooldown.clone();
+            }
+            ApiResponseData::GetCargo(cargo)
+            | ApiResponseData::UpdateCargo { cargo, .. }
+            | ApiResponseData::MarketTransaction { cargo, .. }
+            | ApiResponseData::UpdateContract {
+                cargo: Some(cargo), ..
+            }
+            | ApiResponseData::UpdateConstruction { cargo, .. } => self.cargo = cargo.clone(),
+            ApiResponseData::GetMounts(mounts) => self.mounts = mounts.clone(),
+            ApiResponseData::ModifyMount { mounts, cargo, .. } => {
+                self.mounts = mounts.clone();
+                self.cargo = cargo.clone();
+            }
+            ApiResponseData::GetCooldown(cooldown)
+            | ApiResponseData::CreateSurvey { cooldown, .. }
+            | ApiResponseData::ScanSystems { cooldown, .. }
+            | ApiResponseData::ScanWaypoints { cooldown, .. }
+            | ApiResponseData::ScanShips { cooldown, .. } => self.cooldown = cooldown.clone(),
+            ApiResponseData::Refine {
+                cargo, cooldown, ..
+            }
+            | ApiResponseData::ExtractResources {
+                cargo, cooldown, ..
+            }
+            | ApiResponseData::SiphonResources {
+                cargo, cooldown, ..
+            } => {
+                self.cargo = cargo.clone();
+                self.cooldown = cooldown.clone();
+            }
+            ApiResponseData::RefuelShip { fuel, .. } => self.fuel = fuel.clone(),
+            _ => {}
+        }
+    }
+}
+
 /// Ship cargo details.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipCargo {
     /// The max number of items that can be stored in the cargo hold.
@@ -811,7 +864,7 @@ pub struct ShipCargo {
 }
 
 /// The type of cargo item and the number of units.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipCargoItem {
     /// The good's symbol.
@@ -831,8 +884,8 @@ pub struct ShipCargoItem {
 /// in near perfect condition. As the condition of a component
 /// is repaired, the overall integrity of the component decreases.
 /// >= 0 && <= 1
-#[derive(Serialize, Deserialize, Debug)]
-pub struct ShipComponentCondition(f64);
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct ShipComponentCondition(pub f64);
 
 /// The overall integrity of the component, which determines
 /// the performance of the component. A value of 0 indicates
@@ -841,13 +894,13 @@ pub struct ShipComponentCondition(f64);
 /// condition. The integrity of the component is non-repairable,
 /// and represents permanent wear over time.
 /// >= 0 && <= 1
-#[derive(Serialize, Deserialize, Debug)]
-pub struct ShipComponentIntegrity(f64);
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct ShipComponentIntegrity(pub f64);
 
 /// An event that represents damage or wear to
 /// a ship's reactor, frame, or engine, reducing
 /// the condition of the ship.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipConditionEvent {
     pub symbol: ShipConditionEventType,
@@ -858,7 +911,7 @@ pub struct ShipConditionEvent {
     pub description: String,
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ShipConditionEventType {
     ReactorOverload,
@@ -890,7 +943,7 @@ pub enum ShipConditionEventType {
     AtmosphericEntryHeat,
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ShipComponentType {
     Frame,
@@ -900,7 +953,7 @@ pub enum ShipComponentType {
 
 /// The ship's crew service and maintain the
 /// ship's systems and equipment.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipCrew {
     /// The current number of crew members on the ship.
@@ -923,7 +976,7 @@ pub struct ShipCrew {
     pub wages: u64,
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ShiftType {
     Strict,
@@ -932,7 +985,7 @@ pub enum ShiftType {
 
 /// The engine determines how quickly a ship travels
 /// between waypoints.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipEngine {
     /// The symbol of the engine.
@@ -952,7 +1005,7 @@ pub struct ShipEngine {
     pub requirements: ShipRequirements,
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum EngineType {
     EngineImpulseDriveI,
@@ -961,7 +1014,7 @@ pub enum EngineType {
     EngineHyperDriveI,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipFrame {
     pub symbol: FrameType,
@@ -989,7 +1042,7 @@ pub struct ShipFrame {
     pub requirements: ShipRequirements,
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 #[expect(clippy::enum_variant_names)]
 pub enum FrameType {
@@ -1010,7 +1063,7 @@ pub enum FrameType {
     FrameCarrier,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipFuel {
     pub current: u64,
@@ -1020,7 +1073,7 @@ pub struct ShipFuel {
     pub consumed: Option<FuelConsumption>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct FuelConsumption {
     pub amount: u64,
@@ -1029,7 +1082,7 @@ pub struct FuelConsumption {
 
 /// Result of a transaction for a ship modification,
 /// such as installing a mount or a module.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipModificationTransaction {
     pub waypoint_symbol: String,
@@ -1042,7 +1095,7 @@ pub struct ShipModificationTransaction {
 /// A module can be installed in a ship and provides
 /// a set of capabilities such as storage space or
 /// quarters for crew. Module installations are permanent.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipModule {
     pub symbol: ModuleType,
@@ -1059,7 +1112,7 @@ pub struct ShipModule {
     pub requirements: ShipRequirements,
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ModuleType {
     ModuleMineralProcessorI,
@@ -1084,7 +1137,7 @@ pub enum ModuleType {
     ModuleShieldGeneratorIi,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipMount {
     pub symbol: MountType,
@@ -1099,7 +1152,7 @@ pub struct ShipMount {
     pub requirements: ShipRequirements,
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum MountType {
     MountGasSiphonI,
@@ -1119,7 +1172,7 @@ pub enum MountType {
     MountTurretI,
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum DepositType {
     QuartzSand,
@@ -1139,7 +1192,7 @@ pub enum DepositType {
 }
 
 /// The navigation information of the ship.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipNav {
     pub system_symbol: String,
@@ -1151,7 +1204,7 @@ pub struct ShipNav {
     pub flight_mode: ShipNavFlightMode,
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ShipNavFlightMode {
     Drift,
@@ -1160,7 +1213,7 @@ pub enum ShipNavFlightMode {
     Burn,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipNavRoute {
     pub destination: ShipNavRouteWaypoint,
@@ -1169,7 +1222,7 @@ pub struct ShipNavRoute {
     pub arrival: String, // TODO: This is supposed to be a "date-time". Figure out the correct Rust type for that.
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipNavRouteWaypoint {
     pub symbol: String,
@@ -1180,7 +1233,7 @@ pub struct ShipNavRouteWaypoint {
     pub y: i64,
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ShipNavStatus {
     InTransit,
@@ -1188,7 +1241,19 @@ pub enum ShipNavStatus {
     Docked,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+impl Display for ShipNavStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let res = match self {
+            ShipNavStatus::InTransit => "IN_TRANSIT",
+            ShipNavStatus::InOrbit => "IN_ORBIT",
+            ShipNavStatus::Docked => "DOCKED",
+        };
+
+        write!(f, "{res}")
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipReactor {
     pub symbol: ReactorType,
@@ -1204,7 +1269,7 @@ pub struct ShipReactor {
     pub requirements: ShipRequirements,
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ReactorType {
     ReactorSolarI,
@@ -1214,7 +1279,7 @@ pub enum ReactorType {
     ReactorAntimatterI,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipRegistration {
     pub name: String,
@@ -1223,7 +1288,7 @@ pub struct ShipRegistration {
     pub role: ShipRole,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipRequirements {
     pub power: Option<u64>,
@@ -1231,7 +1296,7 @@ pub struct ShipRequirements {
     pub slots: Option<u64>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ShipRole {
     Fabricator,
@@ -1250,7 +1315,7 @@ pub enum ShipRole {
     Refinery,
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 #[expect(clippy::enum_variant_names)]
 pub enum ShipType {
@@ -1268,13 +1333,13 @@ pub enum ShipType {
     ShipSurveyor,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 pub struct ShipTypeListItem {
     #[serde(rename = "type")]
     pub ship_type: ShipType,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Shipyard {
     pub symbol: String,
@@ -1289,7 +1354,7 @@ pub struct Shipyard {
     pub modifications_fee: u64,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipyardShip {
     #[serde(rename = "type")]
@@ -1307,7 +1372,7 @@ pub struct ShipyardShip {
     pub crew: ShipCrew,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipyardTransaction {
     pub waypoint_symbol: String,
@@ -1318,7 +1383,7 @@ pub struct ShipyardTransaction {
     pub timestamp: String, // TODO: This is supposed to be a "date-time". Figure out the correct Rust type for that.
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Siphon {
     pub ship_symbol: String,
@@ -1326,7 +1391,7 @@ pub struct Siphon {
     pub siphon_yield: SiphonYield,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SiphonYield {
     pub symbol: TradeSymbol,
@@ -1336,7 +1401,7 @@ pub struct SiphonYield {
 /// A resource survey of a waypoint, detailing
 /// a specific extraction location and the types of
 /// resources that can be found there.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Survey {
     /// A unique signature for the location of this survey.
@@ -1348,7 +1413,7 @@ pub struct Survey {
     pub size: DepositSize,
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum DepositSize {
     Small,
@@ -1356,11 +1421,15 @@ pub enum DepositSize {
     Large,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// A deposit that can be found in a survey.
+/// Goods appearing more than once are more likely to be extracted.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
-pub struct SurveyDeposit(String);
+pub struct SurveyDeposit {
+    pub symbol: TradeSymbol,
+}
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct System {
     pub symbol: SystemSymbol,
@@ -1373,47 +1442,29 @@ pub struct System {
     pub factions: Vec<SystemFaction>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum SystemFaction {
-    Cosmic,
-    Void,
-    Galactic,
-    Quantum,
-    Dominion,
-    Astro,
-    Corsairs,
-    Obsidian,
-    Aegis,
-    United,
-    Solitary,
-    Cobalt,
-    Omega,
-    Echo,
-    Lords,
-    Cult,
-    Ancients,
-    Shadow,
-    Ethereal,
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemFaction {
+    pub symbol: FactionSymbol,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
-pub struct SystemSymbol(String);
+pub struct SystemSymbol(pub String);
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SystemWaypoint {
-    symbol: String,
+    pub symbol: String,
     #[serde(rename = "type")]
-    waypoint_type: WaypointType,
-    x: i64,
-    y: i64,
-    orbitals: Vec<WaypointOrbital>,
-    orbits: Option<String>,
+    pub waypoint_type: WaypointType,
+    pub x: i64,
+    pub y: i64,
+    pub orbitals: Vec<WaypointOrbital>,
+    pub orbits: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Waypoint {
     pub symbol: String,
@@ -1431,13 +1482,13 @@ pub struct Waypoint {
     pub is_under_construction: bool,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct WaypointFaction {
     pub symbol: FactionSymbol,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct WaypointModifier {
     pub symbol: WaypointModifierSymbol,
@@ -1445,7 +1496,7 @@ pub struct WaypointModifier {
     pub description: String,
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum WaypointModifierSymbol {
     Stripped,
@@ -1455,17 +1506,17 @@ pub enum WaypointModifierSymbol {
     CivilUnrest,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct WaypointOrbital {
     pub symbol: WaypointSymbol,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct WaypointSymbol(String);
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct WaypointTrait {
     pub symbol: WaypointTraitSymbol,
@@ -1473,7 +1524,7 @@ pub struct WaypointTrait {
     pub description: String,
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum WaypointTraitSymbol {
     Uncharted,
@@ -1625,7 +1676,7 @@ impl Display for WaypointTraitSymbol {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum WaypointType {
     Planet,
@@ -1667,7 +1718,7 @@ impl Display for WaypointType {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, ToSchema)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiStatus {
     pub status: String,
@@ -1681,7 +1732,7 @@ pub struct ApiStatus {
     pub links: Vec<Link>,
 }
 
-#[derive(Serialize, Deserialize, Debug, ToSchema)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct GameStats {
     pub agents: u64,
@@ -1690,49 +1741,49 @@ pub struct GameStats {
     pub waypoints: u64,
 }
 
-#[derive(Serialize, Deserialize, Debug, ToSchema)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Leaderboards {
     pub most_credits: Vec<LeaderboardAgentCredits>,
     pub most_submitted_charts: Vec<LeaderboardAgentCharts>,
 }
 
-#[derive(Serialize, Deserialize, Debug, ToSchema)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct LeaderboardAgentCredits {
     pub agent_symbol: String,
     pub credits: i64,
 }
 
-#[derive(Serialize, Deserialize, Debug, ToSchema)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct LeaderboardAgentCharts {
     pub agent_symbol: String,
     pub chart_count: u64,
 }
 
-#[derive(Serialize, Deserialize, Debug, ToSchema)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ServerResets {
     pub next: String,
     pub frequency: String,
 }
 
-#[derive(Serialize, Deserialize, Debug, ToSchema)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Announcement {
     pub title: String,
     pub body: String,
 }
 
-#[derive(Serialize, Deserialize, Debug, ToSchema)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Link {
     pub name: String,
     pub url: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct RegisterAgent {
     pub faction: FactionSymbol,
@@ -1740,7 +1791,7 @@ pub struct RegisterAgent {
     pub email: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct DeliverCargo {
     pub ship_symbol: String,
@@ -1748,27 +1799,56 @@ pub struct DeliverCargo {
     pub units: u64,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipPurchase {
     pub ship_type: ShipType,
     pub waypoint_symbol: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Produce {
     pub produce: TradeSymbol,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiResponse {
     pub data: ApiResponseData,
     pub meta: Option<Meta>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// The body of a response the API could not fulfill.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiErrorResponse {
+    pub error: ApiError,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiError {
+    pub message: String,
+    /// See <https://docs.spacetraders.io/api-guide/response-errors> for the known codes.
+    pub code: u64,
+    pub data: Option<serde_json::Value>,
+}
+
+impl ApiError {
+    /// Whether a survey was rejected because it has expired or its deposits are exhausted.
+    pub fn is_survey_unusable(&self) -> bool {
+        matches!(self.code, 4221 | 4224)
+    }
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "API error {}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(untagged)]
 pub enum ApiResponseData {
     RegisterAgent(Box<RegisterAgentSuccess>),
@@ -1800,9 +1880,6 @@ pub enum ApiResponseData {
     GetCargo(ShipCargo),
     GetNav(ShipNav),
     GetMounts(Vec<ShipMount>),
-    GetShipTransaction {
-        transaction: ShipTransaction,
-    },
     GetCooldown(Cooldown),
     ShipPurchase {
         agent: Agent,
@@ -1890,9 +1967,21 @@ pub enum ApiResponseData {
         ship: Box<Ship>,
         transaction: ShipTransaction,
     },
+    /// Jettisoning or transferring cargo. Only `cargo` is required,
+    /// which most responses above have as well, so this must come late.
+    #[serde(rename_all = "camelCase")]
+    UpdateCargo {
+        cargo: ShipCargo,
+        /// The cargo of the ship that received a transfer.
+        target_cargo: Option<ShipCargo>,
+    },
+    // Only a transaction matches most responses above, so this must come last.
+    GetShipTransaction {
+        transaction: ShipTransaction,
+    },
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct RegisterAgentSuccess {
     pub agent: Agent,
@@ -1902,7 +1991,7 @@ pub struct RegisterAgentSuccess {
     pub token: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TradeGoodAmount {
     #[serde(rename(serialize = "symbol"))]
@@ -1910,26 +1999,26 @@ pub struct TradeGoodAmount {
     pub units: u64,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Destination {
     pub waypoint_symbol: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct FlightMode {
     pub flight_mode: ShipNavFlightMode,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipRefuel {
     pub units: Option<u64>,
     pub from_cargo: Option<bool>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CargoTransfer {
     pub trade_symbol: TradeSymbol,
@@ -1937,7 +2026,7 @@ pub struct CargoTransfer {
     pub ship_symbol: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ModifyMount {
     pub symbol: MountType,
@@ -0,0 +1,139 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Context;
+use tokio::{
+    sync::Mutex,
+    time::{interval, MissedTickBehavior},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{event, instrument, Level};
+
+use crate::{
+    cache::Cache,
+    client::Client,
+    config::{AgentConfig, ResetConfig},
+    events::{Event, Events},
+    store::Store,
+};
+
+/// The name the date of the current reset is saved under.
+const RESET_DATE: &str = "reset_date";
+/// The environment variable holding the account token, which is needed to register agents.
+const ACCOUNT_TOKEN_VAR: &str = "SPACETRADERS_ACCOUNT_TOKEN";
+
+/// Poll the API status and start over whenever the universe was reset.
+///
+/// The date of the reset the store belongs to is kept in the store itself,
+/// so a reset that happened while catfleet was not running is noticed as well.
+#[instrument(name = "reset_watcher", level = Level::INFO, skip_all)]
+pub async fn watch(
+    config: ResetConfig,
+    agent: Option<AgentConfig>,
+    client: Arc<Mutex<Client>>,
+    store: Arc<Store>,
+    cache: Arc<Cache>,
+    events: Events,
+    shutdown: CancellationToken,
+) -> Result<(), anyhow::Error> {
+    let mut ticker = interval(Duration::from_secs(config.poll_interval_secs.max(1)));
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    // Whether the agent for the current reset still has to be registered.
+    // The reset date is only saved once it was, so that a failure is retried.
+    let mut unregistered = false;
+
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => return Ok(()),
+            _ = ticker.tick() => {}
+        }
+
+        let status = match client.lock().await.get_status().await {
+            Ok(status) => status,
+            Err(err) => {
+                event!(Level::WARN, "Failed to fetch status: {err:?}");
+                continue;
+            }
+        };
+
+        let previous = match store.job_state::<String>(RESET_DATE) {
+            Ok(previous) => previous,
+            Err(err) => {
+                event!(Level::WARN, "Failed to read the reset date: {err:?}");
+                continue;
+            }
+        };
+        match previous {
+            Some(previous) if previous != status.reset_date => {
+                event!(
+                    Level::WARN,
+                    "The universe was reset on {}; starting over",
+                    status.reset_date
+                );
+                if let Err(err) = store.archive(&previous) {
+                    event!(Level::ERROR, "Failed to archive the database: {err:?}");
+                    continue;
+                }
+                cache.clear();
+                unregistered = true;
+
+                events.emit(Event::Reset {
+                    previous_reset_date: previous,
+                    reset_date: status.reset_date.clone(),
+                });
+            }
+            Some(_) => continue,
+            None => {}
+        }
+
+        if unregistered {
+            if let Err(err) = register(agent.as_ref(), &mut *client.lock().await, &store).await {
+                event!(Level::ERROR, "Failed to register agent: {err:?}");
+                continue;
+            }
+            unregistered = false;
+        }
+        if let Err(err) = store.set_job_state(RESET_DATE, &status.reset_date) {
+            event!(Level::WARN, "Failed to save the reset date: {err:?}");
+        }
+    }
+}
+
+/// Replace the token of the old agent, which is no good anymore, with a newly registered one.
+///
+/// Registering uses the account token. If that fails, the client is left without a token
+/// rather than with the account token, so that nothing else acts on behalf of the account.
+/// Without an agent to register there is nothing to retry, so that is not an error.
+async fn register(
+    agent: Option<&AgentConfig>,
+    client: &mut Client,
+    store: &Store,
+) -> Result<(), anyhow::Error> {
+    let Some(agent) = agent else {
+        client.set_token(None)?;
+        event!(
+            Level::WARN,
+            "No agent configured; set a new SPACETRADERS_TOKEN and restart"
+        );
+        return Ok(());
+    };
+
+    client.set_token(std::env::var(ACCOUNT_TOKEN_VAR).ok().as_deref())?;
+    let res = client
+        .register_new_agent(agent.faction, agent.symbol.clone(), agent.email.clone())
+        .await;
+    client.set_token(None)?;
+
+    let registered = res.with_context(|| format!("Registering `{}`", agent.symbol))?;
+    client.set_token(Some(&registered.token))?;
+    event!(Level::INFO, "Registered agent `{}`", agent.symbol);
+    // The agent is registered either way, so registering it again would only fail.
+    if let Err(err) = store.add_agent(&agent.symbol, &registered.token) {
+        event!(
+            Level::WARN,
+            "Failed to keep the token of `{}`: {err:?}",
+            agent.symbol
+        );
+    }
+
+    Ok(())
+}
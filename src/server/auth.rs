@@ -0,0 +1,220 @@
+use std::{
+    future::{ready, Ready},
+    sync::Arc,
+};
+
+use axum::{
+    body::Body,
+    http::{header, HeaderMap, HeaderValue, Method, Request, Response, StatusCode},
+};
+use serde::Deserialize;
+use tower_http::auth::{AsyncAuthorizeRequest, AsyncRequireAuthorizationLayer};
+use tracing::{event, Level};
+use utoipa::{
+    openapi::{
+        security::{Http, HttpAuthScheme, SecurityRequirement, SecurityScheme},
+        Components,
+    },
+    Modify,
+};
+
+use crate::config::{ApiKey, AuthConfig};
+
+/// The name of the security scheme in the OpenAPI document.
+const SECURITY_SCHEME: &str = "api_key";
+
+/// The level of access granted to an API key.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// May only query state, i.e. issue `GET` and `HEAD` requests.
+    ReadOnly,
+    /// May additionally issue commands that change state,
+    /// such as purchasing ships or cargo.
+    Operator,
+}
+
+impl Role {
+    /// The role that is required to issue a request with the given method.
+    fn required_for(method: &Method) -> Self {
+        match *method {
+            Method::GET | Method::HEAD | Method::OPTIONS => Role::ReadOnly,
+            _ => Role::Operator,
+        }
+    }
+}
+
+/// Authorizes requests against the API keys from the configuration.
+///
+/// Clients pass their key as a bearer token in the `Authorization` header.
+#[derive(Debug, Clone)]
+pub struct ApiKeyAuth {
+    keys: Arc<Vec<ApiKey>>,
+}
+
+impl ApiKeyAuth {
+    pub fn layer(config: &AuthConfig) -> AsyncRequireAuthorizationLayer<Self> {
+        if config.api_keys.is_empty() {
+            event!(
+                Level::WARN,
+                "No API keys configured; all authenticated routes will be rejected"
+            );
+        }
+
+        AsyncRequireAuthorizationLayer::new(Self {
+            keys: Arc::new(config.api_keys.clone()),
+        })
+    }
+
+    fn authenticate(&self, headers: &HeaderMap) -> Option<&ApiKey> {
+        let token = headers
+            .get(header::AUTHORIZATION)?
+            .to_str()
+            .ok()?
+            .strip_prefix("Bearer ")?;
+
+        self.keys
+            .iter()
+            .find(|key| constant_time_eq(key.key.as_bytes(), token.as_bytes()))
+    }
+}
+
+impl<B> AsyncAuthorizeRequest<B> for ApiKeyAuth {
+    type RequestBody = B;
+    type ResponseBody = Body;
+    type Future = Ready<Result<Request<B>, Response<Body>>>;
+
+    fn authorize(&mut self, request: Request<B>) -> Self::Future {
+        let Some(key) = self.authenticate(request.headers()) else {
+            event!(Level::DEBUG, uri = %request.uri(), "Rejecting unauthenticated request");
+            let mut res = Response::new(Body::empty());
+            *res.status_mut() = StatusCode::UNAUTHORIZED;
+            res.headers_mut()
+                .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+            return ready(Err(res));
+        };
+
+        let required = Role::required_for(request.method());
+        if key.role < required {
+            event!(
+                Level::WARN,
+                key = key.name,
+                method = %request.method(),
+                uri = %request.uri(),
+                "Rejecting request with insufficient role"
+            );
+            let mut res = Response::new(Body::empty());
+            *res.status_mut() = StatusCode::FORBIDDEN;
+            return ready(Err(res));
+        }
+
+        event!(Level::TRACE, key = key.name, role = ?key.role, "Authorized request");
+
+        ready(Ok(request))
+    }
+}
+
+/// Compare two byte strings without short-circuiting on the first
+/// mismatch, so that the time taken does not leak how much of a key matched.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Registers the API key security scheme in the OpenAPI document
+/// and requires it for all operations.
+pub struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        openapi
+            .components
+            .get_or_insert_with(Components::new)
+            .add_security_scheme(
+                SECURITY_SCHEME,
+                SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)),
+            );
+        openapi.security = Some(vec![SecurityRequirement::new(
+            SECURITY_SCHEME,
+            Vec::<String>::new(),
+        )]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use tower::{service_fn, ServiceBuilder, ServiceExt};
+
+    use super::*;
+
+    fn config() -> AuthConfig {
+        AuthConfig {
+            api_keys: vec![
+                ApiKey {
+                    name: "dashboard".to_string(),
+                    key: "read-key".to_string(),
+                    role: Role::ReadOnly,
+                },
+                ApiKey {
+                    name: "scripts".to_string(),
+                    key: "operator-key".to_string(),
+                    role: Role::Operator,
+                },
+            ],
+        }
+    }
+
+    async fn send(method: Method, token: Option<&str>) -> StatusCode {
+        let service = ServiceBuilder::new()
+            .layer(ApiKeyAuth::layer(&config()))
+            .service(service_fn(|_: Request<Body>| async {
+                Ok::<_, Infallible>(Response::new(Body::empty()))
+            }));
+
+        let mut req = Request::builder().method(method).uri("/status");
+        if let Some(token) = token {
+            req = req.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+
+        service
+            .oneshot(req.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn rejects_missing_or_unknown_key() {
+        assert_eq!(send(Method::GET, None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            send(Method::GET, Some("not-a-key")).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[tokio::test]
+    async fn read_only_key_cannot_issue_commands() {
+        assert_eq!(send(Method::GET, Some("read-key")).await, StatusCode::OK);
+        assert_eq!(
+            send(Method::POST, Some("read-key")).await,
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[tokio::test]
+    async fn operator_key_can_issue_commands() {
+        assert_eq!(
+            send(Method::GET, Some("operator-key")).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            send(Method::POST, Some("operator-key")).await,
+            StatusCode::OK
+        );
+    }
+}
@@ -0,0 +1,27 @@
+use axum::{extract::State, http::StatusCode, Json};
+use chrono::Utc;
+use tracing::{instrument, Level};
+
+use super::{internal_error, AppState};
+use crate::contracts::{self, ContractStatus};
+
+/// Lists our contracts with their remaining deliveries, estimated cost and profit,
+/// the most urgent first.
+///
+/// Costs are estimated from the cheapest price each good was last seen for
+/// at a market in the system of its destination.
+#[utoipa::path(
+    get,
+    path = "/contracts",
+    responses(
+        (status = 200, body = Vec<ContractStatus>)
+    )
+)]
+#[instrument(level = Level::DEBUG, skip(state))]
+pub async fn contracts(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<ContractStatus>>, StatusCode> {
+    let statuses = contracts::statuses(&state.store, Utc::now()).map_err(internal_error)?;
+
+    Ok(Json(statuses))
+}
@@ -0,0 +1,54 @@
+use std::convert::Infallible;
+
+use axum::{
+    extract::State,
+    response::sse::{self, KeepAlive, Sse},
+};
+use tokio::sync::{broadcast::error::RecvError, mpsc};
+use tokio_stream::{wrappers::ReceiverStream, Stream, StreamExt};
+use tracing::{instrument, Level};
+
+use super::AppState;
+
+/// Streams events like resets of the universe as server-sent events.
+///
+/// Each event is a JSON object with a `type` field.
+/// The stream ends when the server shuts down, so that it does not hold up the shutdown.
+#[utoipa::path(
+    get,
+    path = "/events",
+    responses(
+        (status = 200, content_type = "text/event-stream", body = crate::events::Event)
+    )
+)]
+#[instrument(level = Level::DEBUG, skip(state))]
+pub async fn events(
+    State(state): State<AppState>,
+) -> Sse<impl Stream<Item = Result<sse::Event, Infallible>>> {
+    let mut events = state.events.subscribe();
+    let (tx, rx) = mpsc::channel(16);
+    tokio::spawn(async move {
+        loop {
+            let event = tokio::select! {
+                _ = state.shutdown.cancelled() => break,
+                _ = tx.closed() => break,
+                event = events.recv() => event,
+            };
+            match event {
+                Ok(event) => {
+                    if tx.send(event).await.is_err() {
+                        break;
+                    }
+                }
+                // Events missed by a slow client are skipped.
+                Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+
+    let stream = ReceiverStream::new(rx)
+        .filter_map(|event| sse::Event::default().json_data(event).ok().map(Ok));
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
@@ -0,0 +1,70 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use tracing::{instrument, Level};
+
+use super::{internal_error, AppState};
+use crate::fleet::ShipStatus;
+
+/// Lists what each ship driven by the fleet runtime is up to.
+#[utoipa::path(
+    get,
+    path = "/fleet",
+    responses(
+        (status = 200, body = Vec<ShipStatus>)
+    )
+)]
+#[instrument(level = Level::DEBUG, skip(state))]
+pub async fn ships(State(state): State<AppState>) -> Json<Vec<ShipStatus>> {
+    Json(state.fleet.statuses())
+}
+
+/// Pauses a ship once it finished its current step, until it is resumed.
+#[utoipa::path(
+    post,
+    path = "/fleet/{shipSymbol}/pause",
+    params(
+        ("shipSymbol" = String, Path, description = "The symbol of the ship")
+    ),
+    responses(
+        (status = 204)
+    )
+)]
+#[instrument(level = Level::DEBUG, skip(state))]
+pub async fn pause(
+    State(state): State<AppState>,
+    Path(ship_symbol): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    state
+        .fleet
+        .set_paused(&ship_symbol, true)
+        .map_err(internal_error)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Resumes a paused ship.
+#[utoipa::path(
+    post,
+    path = "/fleet/{shipSymbol}/resume",
+    params(
+        ("shipSymbol" = String, Path, description = "The symbol of the ship")
+    ),
+    responses(
+        (status = 204)
+    )
+)]
+#[instrument(level = Level::DEBUG, skip(state))]
+pub async fn resume(
+    State(state): State<AppState>,
+    Path(ship_symbol): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    state
+        .fleet
+        .set_paused(&ship_symbol, false)
+        .map_err(internal_error)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
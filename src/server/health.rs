@@ -0,0 +1,56 @@
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use serde::Serialize;
+use tracing::{instrument, Level};
+
+use super::AppState;
+
+/// Liveness probe. Answers as long as the process is serving requests.
+#[instrument(level = Level::TRACE)]
+pub async fn healthz() -> impl IntoResponse {
+    "ok"
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Readiness {
+    /// The SpaceTraders API answered the last request.
+    upstream_reachable: bool,
+    /// The SpaceTraders API accepted our token on the last authenticated request.
+    token_valid: bool,
+    /// The store is open and our agent or ships were loaded since the last reset.
+    state_loaded: bool,
+}
+
+/// Readiness probe. Answers with `503 Service Unavailable`
+/// until catfleet is able to do useful work.
+#[instrument(level = Level::TRACE, skip(state))]
+pub async fn readyz(State(state): State<AppState>) -> impl IntoResponse {
+    let readiness = Readiness {
+        upstream_reachable: state.metrics.upstream_reachable(),
+        token_valid: state.metrics.token_valid(),
+        state_loaded: state.cache.loaded(),
+    };
+
+    let status = if readiness.upstream_reachable && readiness.token_valid && readiness.state_loaded
+    {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (status, Json(readiness))
+}
+
+/// Metrics in the Prometheus text exposition format.
+#[instrument(level = Level::TRACE, skip(state))]
+pub async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics.render(),
+    )
+}
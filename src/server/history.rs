@@ -0,0 +1,29 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use tracing::{instrument, Level};
+
+use super::{internal_error, AppState};
+use crate::store::{HistoryQuery, HistorySample};
+
+/// Lists periodic samples of the game stats, the leaderboards,
+/// and our credits and rank, oldest first.
+#[utoipa::path(
+    get,
+    path = "/history",
+    params(HistoryQuery),
+    responses(
+        (status = 200, body = Vec<HistorySample>)
+    )
+)]
+#[instrument(level = Level::DEBUG, skip(state))]
+pub async fn history(
+    State(state): State<AppState>,
+    Query(query): Query<HistoryQuery>,
+) -> Result<Json<Vec<HistorySample>>, StatusCode> {
+    let history = state.store.history(&query).map_err(internal_error)?;
+
+    Ok(Json(history))
+}
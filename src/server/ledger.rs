@@ -0,0 +1,148 @@
+use std::fmt::Write;
+
+use axum::{
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use tracing::{instrument, Level};
+
+use super::{internal_error, AppState};
+use crate::store::{LedgerEntry, LedgerQuery, Report};
+
+/// Lists every recorded transaction, oldest first.
+#[utoipa::path(
+    get,
+    path = "/ledger",
+    params(LedgerQuery),
+    responses(
+        (status = 200, body = Vec<LedgerEntry>)
+    )
+)]
+#[instrument(level = Level::DEBUG, skip(state))]
+pub async fn entries(
+    State(state): State<AppState>,
+    Query(query): Query<LedgerQuery>,
+) -> Result<Json<Vec<LedgerEntry>>, StatusCode> {
+    let entries = state.store.ledger(&query).map_err(internal_error)?;
+
+    Ok(Json(entries))
+}
+
+/// Returns the profit and loss per ship and trade route,
+/// fuel spend, and credits earned per hour.
+#[utoipa::path(
+    get,
+    path = "/ledger/report",
+    params(LedgerQuery),
+    responses(
+        (status = 200, body = Report)
+    )
+)]
+#[instrument(level = Level::DEBUG, skip(state))]
+pub async fn report(
+    State(state): State<AppState>,
+    Query(query): Query<LedgerQuery>,
+) -> Result<Json<Report>, StatusCode> {
+    let report = state.store.report(&query).map_err(internal_error)?;
+
+    Ok(Json(report))
+}
+
+/// Exports the ledger as CSV, for use in a spreadsheet.
+#[utoipa::path(
+    get,
+    path = "/ledger/export.csv",
+    params(LedgerQuery),
+    responses(
+        (status = 200, content_type = "text/csv", body = String)
+    )
+)]
+#[instrument(level = Level::DEBUG, skip(state))]
+pub async fn export_csv(
+    State(state): State<AppState>,
+    Query(query): Query<LedgerQuery>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let entries = state.store.ledger(&query).map_err(internal_error)?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"ledger.csv\"",
+            ),
+        ],
+        csv(&entries),
+    ))
+}
+
+fn csv(entries: &[LedgerEntry]) -> String {
+    let mut out = String::from(
+        "recorded_at,timestamp,kind,ship_symbol,contract_id,waypoint_symbol,trade_symbol,units,credits,task\n",
+    );
+
+    for entry in entries {
+        let kind = serde_json::to_value(entry.kind).unwrap_or_default();
+        let trade_symbol = entry
+            .trade_symbol
+            .map(|t| serde_json::to_value(t).unwrap_or_default());
+        let _ = writeln!(
+            out,
+            "{},{},{},{},{},{},{},{},{},{}",
+            entry.recorded_at,
+            field(entry.timestamp.as_deref()),
+            field(kind.as_str()),
+            field(entry.ship_symbol.as_deref()),
+            field(entry.contract_id.as_deref()),
+            field(entry.waypoint_symbol.as_deref()),
+            field(trade_symbol.as_ref().and_then(|t| t.as_str())),
+            entry.units.map(|u| u.to_string()).unwrap_or_default(),
+            entry.credits,
+            field(entry.task.as_deref()),
+        );
+    }
+
+    out
+}
+
+/// Quote a CSV field if it contains anything that would break the row.
+fn field(value: Option<&str>) -> String {
+    match value {
+        Some(value) if value.contains([',', '"', '\n', '\r']) => {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        }
+        Some(value) => value.to_string(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::LedgerKind;
+
+    #[test]
+    fn renders_csv_rows() {
+        let entries = [LedgerEntry {
+            recorded_at: 1000,
+            timestamp: None,
+            kind: LedgerKind::ContractAccepted,
+            ship_symbol: None,
+            contract_id: Some("abc".to_string()),
+            waypoint_symbol: None,
+            trade_symbol: None,
+            units: None,
+            credits: 5000,
+            task: Some("contracts, \"main\"".to_string()),
+        }];
+
+        let csv = csv(&entries);
+
+        assert_eq!(
+            csv.lines().nth(1).unwrap(),
+            "1000,,contract_accepted,,abc,,,,5000,\"contracts, \"\"main\"\"\""
+        );
+    }
+}
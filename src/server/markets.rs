@@ -0,0 +1,31 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use tracing::{instrument, Level};
+
+use super::{internal_error, AppState};
+use crate::store::{PriceQuery, PriceRecord};
+
+/// Returns the recorded prices of trade goods over time, oldest first.
+///
+/// A record is written for every trade good whenever a market is fetched
+/// while one of our ships is present.
+#[utoipa::path(
+    get,
+    path = "/markets/history",
+    params(PriceQuery),
+    responses(
+        (status = 200, body = Vec<PriceRecord>)
+    )
+)]
+#[instrument(level = Level::DEBUG, skip(state))]
+pub async fn price_history(
+    State(state): State<AppState>,
+    Query(query): Query<PriceQuery>,
+) -> Result<Json<Vec<PriceRecord>>, StatusCode> {
+    let history = state.store.price_history(&query).map_err(internal_error)?;
+
+    Ok(Json(history))
+}
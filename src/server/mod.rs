@@ -1,44 +1,140 @@
 use std::sync::Arc;
 
-use axum::{extract::State, response::IntoResponse, routing::get, Json};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{any, get},
+    Json, Router,
+};
 use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
 use tracing::{event, instrument, Level};
 use utoipa::OpenApi;
 use utoipa_axum::{router::OpenApiRouter, routes};
 
-use crate::client::Client;
+use crate::{
+    cache::Cache, client::Client, config::ServerConfig, events::Events, fleet::Fleet,
+    metrics::Metrics, store::Store,
+};
+use auth::{ApiKeyAuth, SecurityAddon};
+
+pub use auth::Role;
+
+mod auth;
+mod contracts;
+mod events;
+mod fleet;
+mod health;
+mod history;
+mod ledger;
+mod markets;
+mod proxy;
+mod snapshot;
+mod surveys;
+mod universe;
+mod views;
 
 #[derive(OpenApi)]
-#[openapi()]
+#[openapi(modifiers(&SecurityAddon))]
 struct ApiDoc;
 
 #[derive(Clone)]
 struct AppState {
     http_client: Arc<Mutex<Client>>,
+    metrics: Arc<Metrics>,
+    cache: Arc<Cache>,
+    store: Arc<Store>,
+    events: Events,
+    fleet: Arc<Fleet>,
+    /// Cancelled when the server shuts down, to end long-lived responses.
+    shutdown: CancellationToken,
 }
 
-#[instrument(name = "catfleet_server", level = Level::INFO)]
-pub async fn start() {
-    let client = Arc::new(Mutex::new(Client::new().await.unwrap()));
+/// Run the dashboard server until `shutdown` is cancelled.
+/// In-flight requests are allowed to complete before this returns.
+#[instrument(name = "catfleet_server", level = Level::INFO, skip_all)]
+pub async fn start(
+    config: ServerConfig,
+    client: Arc<Mutex<Client>>,
+    store: Arc<Store>,
+    cache: Arc<Cache>,
+    events: Events,
+    fleet: Arc<Fleet>,
+    shutdown: CancellationToken,
+) -> Result<(), anyhow::Error> {
+    let metrics = {
+        let mut client = client.lock().await;
+
+        // Fetch the agent once, so that we know early whether our token is valid.
+        if let Err(err) = client.get_agent().await {
+            event!(Level::WARN, "Failed to fetch agent: {err:?}");
+        }
+
+        client.metrics()
+    };
+
     let state = AppState {
         http_client: client,
+        metrics,
+        cache,
+        store,
+        events,
+        fleet,
+        shutdown: shutdown.clone(),
     };
 
     let (app, openapi) = OpenApiRouter::with_openapi(ApiDoc::openapi())
         .routes(routes!(status))
-        .with_state(state)
+        .routes(routes!(views::ship_views))
+        .routes(routes!(universe::stored_waypoints))
+        .routes(routes!(markets::price_history))
+        .routes(routes!(ledger::entries))
+        .routes(routes!(ledger::report))
+        .routes(routes!(ledger::export_csv))
+        .routes(routes!(surveys::ranked_surveys))
+        .routes(routes!(contracts::contracts))
+        .routes(routes!(history::history))
+        .routes(routes!(fleet::ships))
+        .routes(routes!(fleet::pause))
+        .routes(routes!(fleet::resume))
+        .routes(routes!(events::events))
+        .routes(routes!(snapshot::snapshot))
+        .with_state(state.clone())
         .split_for_parts();
 
-    let app = app.route(
-        "/api-docs/openapi.json",
-        get(move || async { Json(openapi) }),
+    let app = app.merge(
+        Router::new()
+            .route(proxy::PREFIX, any(proxy::proxy))
+            .route(&format!("{}/{{*path}}", proxy::PREFIX), any(proxy::proxy))
+            .with_state(state.clone()),
     );
 
-    let address = "127.0.0.1:3000";
-    let listener = tokio::net::TcpListener::bind(address).await.unwrap();
+    // Everything that is registered after this layer is reachable without authentication.
+    let app = app
+        .layer(ApiKeyAuth::layer(&config.auth))
+        .route(
+            "/api-docs/openapi.json",
+            get(move || async { Json(openapi) }),
+        )
+        .merge(
+            Router::new()
+                .route("/healthz", get(health::healthz))
+                .route("/readyz", get(health::readyz))
+                .route("/metrics", get(health::metrics))
+                .with_state(state),
+        );
+
+    let address = config.address;
+    let listener = tokio::net::TcpListener::bind(&address).await?;
 
     event!(Level::INFO, "Starting server on `http://{address}`");
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown.cancelled_owned())
+        .await?;
+
+    event!(Level::INFO, "Server stopped");
+    Ok(())
 }
 
 /// Returns the SpaceTraders API status.
@@ -55,3 +151,8 @@ async fn status(State(state): State<AppState>) -> impl IntoResponse {
 
     Json(status)
 }
+
+fn internal_error(err: anyhow::Error) -> StatusCode {
+    event!(Level::ERROR, "Failed to query store: {err:?}");
+    StatusCode::INTERNAL_SERVER_ERROR
+}
@@ -0,0 +1,97 @@
+use axum::{
+    body::{Body, Bytes},
+    extract::{Request, State},
+    http::{header, StatusCode, Uri},
+    response::{IntoResponse, Response},
+};
+use http_body_util::{BodyExt, Full};
+use tracing::{event, instrument, Level};
+
+use super::AppState;
+use crate::client;
+
+/// The prefix under which the SpaceTraders API is proxied.
+pub const PREFIX: &str = "/proxy/v2";
+
+/// Request headers that are passed on to the SpaceTraders API.
+/// Notably, this excludes `Authorization`, which carries our
+/// own API key and is replaced with the SpaceTraders token.
+const FORWARDED_HEADERS: [header::HeaderName; 2] = [header::ACCEPT, header::CONTENT_TYPE];
+
+/// Forwards a request to the SpaceTraders API through the shared client,
+/// so that it counts against the same rate limit as everything else.
+#[instrument(level = Level::DEBUG, skip_all, fields(method =% req.method(), uri =% req.uri()))]
+pub async fn proxy(State(state): State<AppState>, req: Request) -> Response {
+    let (parts, body) = req.into_parts();
+
+    let Some(uri) = upstream_uri(&parts.uri) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+
+    let body = match body.collect().await {
+        Ok(body) => body.to_bytes(),
+        Err(err) => {
+            event!(Level::DEBUG, "Failed to read request body: {err}");
+            return StatusCode::BAD_REQUEST.into_response();
+        }
+    };
+
+    let mut upstream_req = hyper::Request::builder().method(parts.method).uri(uri);
+    for name in FORWARDED_HEADERS {
+        if let Some(value) = parts.headers.get(&name) {
+            upstream_req = upstream_req.header(name, value);
+        }
+    }
+    let upstream_req = match upstream_req.body(Full::<Bytes>::new(body)) {
+        Ok(req) => req,
+        Err(_) => return StatusCode::BAD_REQUEST.into_response(),
+    };
+
+    let mut client = state.http_client.lock().await;
+    let res = client::in_task("proxy", client.forward(upstream_req)).await;
+    match res {
+        Ok(res) => res.map(Body::new),
+        Err(err) => {
+            event!(Level::WARN, "Proxied request failed: {err:?}");
+            StatusCode::BAD_GATEWAY.into_response()
+        }
+    }
+}
+
+/// Strip the proxy prefix off a request URI, leaving the path and query
+/// relative to the base URL of the SpaceTraders API.
+fn upstream_uri(uri: &Uri) -> Option<Uri> {
+    let path_and_query = uri.path_and_query()?.as_str().strip_prefix(PREFIX)?;
+
+    if path_and_query.is_empty() || path_and_query.starts_with('?') {
+        Uri::try_from(format!("/{path_and_query}")).ok()
+    } else if path_and_query.starts_with('/') {
+        Uri::try_from(path_and_query).ok()
+    } else {
+        // Something like `/proxy/v2foo`, which is not ours to forward.
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_prefix_and_keeps_query() {
+        let uri = Uri::from_static("/proxy/v2/my/ships?limit=20&page=2");
+        assert_eq!(upstream_uri(&uri).unwrap(), "/my/ships?limit=20&page=2");
+    }
+
+    #[test]
+    fn proxies_status_at_root() {
+        assert_eq!(upstream_uri(&Uri::from_static("/proxy/v2")).unwrap(), "/");
+        assert_eq!(upstream_uri(&Uri::from_static("/proxy/v2/")).unwrap(), "/");
+    }
+
+    #[test]
+    fn rejects_foreign_paths() {
+        assert!(upstream_uri(&Uri::from_static("/proxy/v2foo")).is_none());
+        assert!(upstream_uri(&Uri::from_static("/status")).is_none());
+    }
+}
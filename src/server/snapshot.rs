@@ -0,0 +1,59 @@
+use axum::{
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use tracing::{instrument, Level};
+use utoipa::IntoParams;
+
+use super::{internal_error, AppState};
+use crate::snapshot::{Format, Snapshot};
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SnapshotQuery {
+    /// `json` (the default) or `binary`.
+    format: Option<Format>,
+}
+
+/// Downloads a snapshot of everything known about the fleet,
+/// which can be loaded into a separate database with `catfleet snapshot import <path> <database>`.
+#[utoipa::path(
+    get,
+    path = "/snapshot",
+    params(SnapshotQuery),
+    responses(
+        (status = 200, content_type = "application/json", body = Snapshot),
+        (status = 200, content_type = "application/octet-stream", body = Vec<u8>)
+    )
+)]
+#[instrument(level = Level::DEBUG, skip(state))]
+pub async fn snapshot(
+    State(state): State<AppState>,
+    Query(query): Query<SnapshotQuery>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let format = query.format.unwrap_or_default();
+    let bytes = Snapshot::take(&state.store)
+        .and_then(|snapshot| snapshot.encode(format))
+        .map_err(internal_error)?;
+
+    let (content_type, disposition) = match format {
+        Format::Json => (
+            "application/json",
+            "attachment; filename=\"catfleet-snapshot.json\"",
+        ),
+        Format::Binary => (
+            "application/octet-stream",
+            "attachment; filename=\"catfleet-snapshot.bin\"",
+        ),
+    };
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        bytes,
+    ))
+}
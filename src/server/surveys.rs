@@ -0,0 +1,36 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use tracing::{instrument, Level};
+
+use super::{internal_error, AppState};
+use crate::store::RankedSurvey;
+
+/// Lists the usable surveys of a waypoint, most valuable first.
+///
+/// Surveys are valued by the best price their deposits currently
+/// sell for at the markets of the system.
+#[utoipa::path(
+    get,
+    path = "/surveys/{waypointSymbol}",
+    params(
+        ("waypointSymbol" = String, Path, description = "The symbol of the surveyed waypoint")
+    ),
+    responses(
+        (status = 200, body = Vec<RankedSurvey>)
+    )
+)]
+#[instrument(level = Level::DEBUG, skip(state))]
+pub async fn ranked_surveys(
+    State(state): State<AppState>,
+    Path(waypoint_symbol): Path<String>,
+) -> Result<Json<Vec<RankedSurvey>>, StatusCode> {
+    let surveys = state
+        .store
+        .ranked_surveys(&waypoint_symbol)
+        .map_err(internal_error)?;
+
+    Ok(Json(surveys))
+}
@@ -0,0 +1,36 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use tracing::{instrument, Level};
+
+use super::{internal_error, AppState};
+use crate::model::Waypoint;
+
+/// Lists the waypoints of a system that catfleet has seen so far.
+///
+/// This is served from the store only and never asks the SpaceTraders API,
+/// so the list may be incomplete or outdated.
+#[utoipa::path(
+    get,
+    path = "/systems/{systemSymbol}/waypoints",
+    params(
+        ("systemSymbol" = String, Path, description = "The symbol of the system")
+    ),
+    responses(
+        (status = 200, body = Vec<Waypoint>)
+    )
+)]
+#[instrument(level = Level::DEBUG, skip(state))]
+pub async fn stored_waypoints(
+    State(state): State<AppState>,
+    Path(system_symbol): Path<String>,
+) -> Result<Json<Vec<Waypoint>>, StatusCode> {
+    let waypoints = state
+        .store
+        .waypoints_in_system(&system_symbol)
+        .map_err(internal_error)?;
+
+    Ok(Json(waypoints.into_iter().map(|w| w.value).collect()))
+}
@@ -0,0 +1,190 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{event, instrument, Level};
+use utoipa::{IntoParams, ToSchema};
+
+use super::AppState;
+use crate::model::{ContractDeliverGood, Market, Ship, Waypoint, WaypointTraitSymbol};
+
+/// How old cached objects may be before they are fetched again.
+const MAX_AGE_SHIP: Duration = Duration::from_secs(30);
+const MAX_AGE_WAYPOINT: Duration = Duration::from_secs(60 * 60);
+const MAX_AGE_MARKET: Duration = Duration::from_secs(5 * 60);
+const MAX_AGE_CONTRACTS: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ShipViewQuery {
+    /// Comma separated symbols of the ships to resolve.
+    /// Resolves all of our ships if omitted.
+    symbols: Option<String>,
+}
+
+/// A ship together with everything relevant at its current location.
+#[derive(Serialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ShipView {
+    pub ship: Ship,
+    /// The waypoint the ship is currently at, or travelling to.
+    pub waypoint: Waypoint,
+    /// The market at the waypoint, if there is one.
+    pub market: Option<Market>,
+    /// Deliveries of accepted, unfulfilled contracts that are either
+    /// destined for this waypoint or involve goods in the ship's cargo.
+    pub deliveries: Vec<ContractDelivery>,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractDelivery {
+    pub contract_id: String,
+    #[serde(flatten)]
+    pub delivery: ContractDeliverGood,
+}
+
+/// Resolves composite views of ships in one request.
+///
+/// Objects that were fetched recently are served from the cache
+/// instead of asking the SpaceTraders API again.
+#[utoipa::path(
+    get,
+    path = "/views/ships",
+    params(ShipViewQuery),
+    responses(
+        (status = 200, body = Vec<ShipView>),
+        (status = 502, description = "The SpaceTraders API could not be reached")
+    )
+)]
+#[instrument(level = Level::DEBUG, skip(state))]
+pub async fn ship_views(
+    State(state): State<AppState>,
+    Query(query): Query<ShipViewQuery>,
+) -> Result<Json<Vec<ShipView>>, StatusCode> {
+    let client = &state.http_client;
+    let cache = &state.cache;
+
+    // Only lock the client for each request that can't be served from the cache,
+    // so that other users of the client aren't blocked by a whole view.
+    let ships = match query.symbols {
+        Some(symbols) => {
+            let mut ships = Vec::new();
+            for symbol in symbols.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                let ship = match cache.ship(symbol, MAX_AGE_SHIP) {
+                    Some(ship) => ship,
+                    None => *client
+                        .lock()
+                        .await
+                        .get_ship(symbol.to_string())
+                        .await
+                        .map_err(bad_gateway)?,
+                };
+                ships.push(ship);
+            }
+            ships
+        }
+        None => match cache.ships(MAX_AGE_SHIP) {
+            Some(ships) => ships,
+            None => {
+                let ships = client
+                    .lock()
+                    .await
+                    .list_all_ships()
+                    .await
+                    .map_err(bad_gateway)?;
+                cache.set_ships(ships.clone());
+                ships
+            }
+        },
+    };
+
+    let contracts = match cache.contracts(MAX_AGE_CONTRACTS) {
+        Some(contracts) => contracts,
+        None => {
+            let contracts = client
+                .lock()
+                .await
+                .list_all_contracts()
+                .await
+                .map_err(bad_gateway)?;
+            cache.set_contracts(contracts.clone());
+            contracts
+        }
+    };
+
+    let mut views = Vec::with_capacity(ships.len());
+    for ship in ships {
+        let symbol = &ship.nav.waypoint_symbol;
+
+        let waypoint = match cache.waypoint(symbol, MAX_AGE_WAYPOINT) {
+            Some(waypoint) => waypoint,
+            None => client
+                .lock()
+                .await
+                .get_waypoint(symbol.clone())
+                .await
+                .map_err(bad_gateway)?,
+        };
+
+        let has_market = waypoint
+            .traits
+            .iter()
+            .any(|t| matches!(t.symbol, WaypointTraitSymbol::Marketplace));
+        let market = match cache.market(symbol, MAX_AGE_MARKET) {
+            Some(market) => Some(market),
+            None if has_market => Some(
+                client
+                    .lock()
+                    .await
+                    .get_market(symbol.clone())
+                    .await
+                    .map_err(bad_gateway)?,
+            ),
+            None => None,
+        };
+
+        let deliveries = contracts
+            .iter()
+            .filter(|c| c.accepted && !c.fulfilled)
+            .flat_map(|c| {
+                c.terms
+                    .deliver
+                    .iter()
+                    .flatten()
+                    .map(move |d| (c.id.clone(), d))
+            })
+            .filter(|(_, d)| d.units_fulfilled < d.units_required)
+            .filter(|(_, d)| {
+                d.destination_symbol == *symbol
+                    || ship
+                        .cargo
+                        .inventory
+                        .iter()
+                        .any(|item| item.symbol == d.trade_symbol)
+            })
+            .map(|(contract_id, d)| ContractDelivery {
+                contract_id,
+                delivery: d.clone(),
+            })
+            .collect();
+
+        views.push(ShipView {
+            ship,
+            waypoint,
+            market,
+            deliveries,
+        });
+    }
+
+    Ok(Json(views))
+}
+
+fn bad_gateway(err: anyhow::Error) -> StatusCode {
+    event!(Level::WARN, "Upstream request failed: {err:?}");
+    StatusCode::BAD_GATEWAY
+}
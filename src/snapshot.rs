@@ -0,0 +1,239 @@
+use std::path::Path;
+
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+use tracing::{event, instrument, Level};
+use utoipa::ToSchema;
+
+use crate::{
+    model::{Agent, Contract, Market, Ship, Survey},
+    store::{Store, Stored},
+};
+
+/// The version of the snapshot format.
+/// Bump this whenever a change to the model breaks reading older snapshots.
+pub const VERSION: u32 = 1;
+
+/// Binary snapshots start with these bytes, followed by the version
+/// as big endian `u32` and the snapshot encoded as MessagePack.
+const MAGIC: &[u8; 8] = b"CATFLEET";
+
+/// Everything catfleet knows about our fleet and its surroundings at one point in time.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    pub version: u32,
+    /// When the snapshot was taken, in milliseconds since the Unix epoch.
+    pub taken_at: i64,
+    pub agent: Option<Agent>,
+    pub ships: Vec<Ship>,
+    pub contracts: Vec<Contract>,
+    pub markets: Vec<Market>,
+    /// Surveys that had not expired when the snapshot was taken.
+    pub surveys: Vec<Survey>,
+}
+
+/// The encoding of a snapshot file.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    #[default]
+    Json,
+    /// MessagePack, with structs encoded as arrays.
+    Binary,
+}
+
+impl Format {
+    /// `.json` files are JSON, everything else is binary.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension() {
+            Some(extension) if extension.eq_ignore_ascii_case("json") => Format::Json,
+            _ => Format::Binary,
+        }
+    }
+}
+
+fn values<T>(stored: Vec<Stored<T>>) -> Vec<T> {
+    stored.into_iter().map(|stored| stored.value).collect()
+}
+
+impl Snapshot {
+    /// Collect the current fleet state from the store.
+    pub fn take(store: &Store) -> Result<Self, anyhow::Error> {
+        Ok(Self {
+            version: VERSION,
+            taken_at: chrono::Utc::now().timestamp_millis(),
+            agent: store
+                .all::<Agent>()?
+                .into_iter()
+                .max_by_key(|agent| agent.fetched_at)
+                .map(|agent| agent.value),
+            ships: values(store.all()?),
+            contracts: values(store.all()?),
+            markets: values(store.all()?),
+            surveys: store.all_surveys()?,
+        })
+    }
+
+    /// Put everything in the snapshot into the store,
+    /// as fetched at the time the snapshot was taken.
+    pub fn restore(&self, store: &Store) -> Result<(), anyhow::Error> {
+        store.put_all_fetched_at(self.agent.as_slice(), self.taken_at)?;
+        store.put_all_fetched_at(&self.ships, self.taken_at)?;
+        store.put_all_fetched_at(&self.contracts, self.taken_at)?;
+        store.put_all_fetched_at(&self.markets, self.taken_at)?;
+        for survey in &self.surveys {
+            store.put_survey(survey)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn encode(&self, format: Format) -> Result<Vec<u8>, anyhow::Error> {
+        match format {
+            Format::Json => Ok(serde_json::to_vec_pretty(self)?),
+            Format::Binary => {
+                let mut bytes = MAGIC.to_vec();
+                bytes.extend_from_slice(&self.version.to_be_bytes());
+                rmp_serde::encode::write(&mut bytes, self)?;
+                Ok(bytes)
+            }
+        }
+    }
+
+    /// Decode a snapshot, refusing snapshots of other versions.
+    pub fn decode(bytes: &[u8], format: Format) -> Result<Self, anyhow::Error> {
+        #[derive(Deserialize)]
+        struct Header {
+            version: u32,
+        }
+
+        let version = match format {
+            Format::Json => serde_json::from_slice::<Header>(bytes)?.version,
+            Format::Binary => {
+                let Some(rest) = bytes.strip_prefix(MAGIC.as_slice()) else {
+                    bail!("Not a catfleet snapshot");
+                };
+                let version = rest.get(..4).context("Snapshot is truncated")?;
+                u32::from_be_bytes(version.try_into()?)
+            }
+        };
+        if version != VERSION {
+            bail!("Snapshot has version {version}, but only version {VERSION} is supported");
+        }
+
+        Ok(match format {
+            Format::Json => serde_json::from_slice(bytes)?,
+            Format::Binary => rmp_serde::from_slice(&bytes[MAGIC.len() + 4..])?,
+        })
+    }
+}
+
+/// Write a snapshot of the store to `path`, in the format its extension implies.
+#[instrument(level = Level::INFO, skip(store))]
+pub fn export(store: &Store, path: &Path) -> Result<(), anyhow::Error> {
+    let snapshot = Snapshot::take(store)?;
+    let bytes = snapshot.encode(Format::from_path(path))?;
+    std::fs::write(path, &bytes).with_context(|| format!("Writing `{}`", path.display()))?;
+
+    event!(
+        Level::INFO,
+        "Exported {} ships, {} contracts, {} markets and {} surveys",
+        snapshot.ships.len(),
+        snapshot.contracts.len(),
+        snapshot.markets.len(),
+        snapshot.surveys.len()
+    );
+    Ok(())
+}
+
+/// Load a snapshot from `path` into the database at `database`, for looking at it offline.
+/// The live database is left alone, so that old data never shadows what the API reports.
+#[instrument(level = Level::INFO)]
+pub fn import(path: &Path, database: &Path, live: &Path) -> Result<(), anyhow::Error> {
+    let same = match (std::fs::canonicalize(database), std::fs::canonicalize(live)) {
+        (Ok(database), Ok(live)) => database == live,
+        _ => database == live,
+    };
+    if same {
+        bail!("Refusing to import a snapshot into the live database");
+    }
+
+    let bytes = std::fs::read(path).with_context(|| format!("Reading `{}`", path.display()))?;
+    let snapshot = Snapshot::decode(&bytes, Format::from_path(path))
+        .with_context(|| format!("Decoding `{}`", path.display()))?;
+    let store = Store::open(database)?;
+    snapshot.restore(&store)?;
+    store.checkpoint()?;
+
+    event!(
+        Level::INFO,
+        "Imported snapshot taken at {}",
+        snapshot.taken_at
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> Snapshot {
+        let ship = crate::model::fixtures::ship("CAT-1", "X1-A-1");
+        Snapshot {
+            version: VERSION,
+            taken_at: 1234,
+            agent: None,
+            ships: vec![ship],
+            contracts: Vec::new(),
+            markets: Vec::new(),
+            surveys: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn round_trips_in_both_formats() {
+        for format in [Format::Json, Format::Binary] {
+            let bytes = snapshot().encode(format).unwrap();
+            let decoded = Snapshot::decode(&bytes, format).unwrap();
+
+            assert_eq!(decoded.taken_at, 1234);
+            assert_eq!(decoded.ships.len(), 1);
+            assert_eq!(decoded.ships[0].symbol, snapshot().ships[0].symbol);
+        }
+
+        let json = snapshot().encode(Format::Json).unwrap();
+        let binary = snapshot().encode(Format::Binary).unwrap();
+        assert!(binary.len() < json.len());
+    }
+
+    #[test]
+    fn restores_what_was_taken() {
+        let store = Store::open_in_memory().unwrap();
+        snapshot().restore(&store).unwrap();
+
+        let taken = Snapshot::take(&store).unwrap();
+
+        assert_eq!(taken.version, VERSION);
+        assert_eq!(taken.ships.len(), 1);
+        assert!(taken.contracts.is_empty());
+
+        let ship = store.get::<Ship>("CAT-1").unwrap().unwrap();
+        assert_eq!(
+            ship.fetched_at,
+            std::time::UNIX_EPOCH + std::time::Duration::from_millis(1234)
+        );
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let mut snapshot = snapshot();
+        snapshot.version = VERSION + 1;
+
+        for format in [Format::Json, Format::Binary] {
+            let bytes = snapshot.encode(format).unwrap();
+            assert!(Snapshot::decode(&bytes, format).is_err());
+        }
+        assert!(Snapshot::decode(b"garbage", Format::Binary).is_err());
+    }
+}
@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::Store;
+use crate::model::{Agent, ApiStatus, LeaderboardAgentCharts, LeaderboardAgentCredits};
+
+const CREDITS: &str = "credits";
+const CHARTS: &str = "charts";
+
+/// The game and our agent at one point in time.
+#[derive(Serialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HistorySample {
+    /// When the sample was taken, in milliseconds since the Unix epoch.
+    pub recorded_at: i64,
+    pub agents: u64,
+    pub ships: u64,
+    pub systems: u64,
+    pub waypoints: u64,
+    /// Our agent, if it could be fetched.
+    pub agent_symbol: Option<String>,
+    pub credits: Option<i64>,
+    pub ship_count: Option<u64>,
+    /// Our place among all agents by credits, starting at 1.
+    /// Falls back to the credits leaderboard if the agents could not be listed,
+    /// and is `None` if we did not make it onto the leaderboard either.
+    pub credits_rank: Option<u32>,
+    /// Our place on the submitted charts leaderboard, starting at 1.
+    pub charts_rank: Option<u32>,
+    pub most_credits: Vec<LeaderboardAgentCredits>,
+    pub most_submitted_charts: Vec<LeaderboardAgentCharts>,
+}
+
+/// Filters for the history. Both are optional.
+#[derive(Deserialize, Debug, Default, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct HistoryQuery {
+    /// Only samples at or after this time, in milliseconds since the Unix epoch.
+    pub since: Option<i64>,
+    /// Only samples before this time, in milliseconds since the Unix epoch.
+    pub until: Option<i64>,
+}
+
+impl Store {
+    /// Record the game stats and leaderboards of `status`, along with our agent
+    /// and its place among all agents by credits, at `recorded_at` milliseconds
+    /// since the Unix epoch. A later sample at the same time replaces the earlier one.
+    pub fn record_history(
+        &self,
+        recorded_at: i64,
+        status: &ApiStatus,
+        agent: Option<&Agent>,
+        credits_rank: Option<u32>,
+    ) -> Result<(), anyhow::Error> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT OR REPLACE INTO status_history (
+                recorded_at, agents, ships, systems, waypoints, agent_symbol, credits, ship_count,
+                credits_rank
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                recorded_at,
+                status.stats.agents,
+                status.stats.ships,
+                status.stats.systems,
+                status.stats.waypoints,
+                agent.map(|agent| &agent.symbol),
+                agent.map(|agent| agent.credits),
+                agent.map(|agent| agent.ship_count),
+                credits_rank,
+            ],
+        )?;
+        tx.execute(
+            "DELETE FROM leaderboard_history WHERE recorded_at = ?1",
+            params![recorded_at],
+        )?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT OR REPLACE INTO leaderboard_history (
+                    recorded_at, board, rank, agent_symbol, value
+                ) VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            let boards = &status.leaderboards;
+            for (rank, entry) in boards.most_credits.iter().enumerate() {
+                stmt.execute(params![
+                    recorded_at,
+                    CREDITS,
+                    rank + 1,
+                    entry.agent_symbol,
+                    entry.credits
+                ])?;
+            }
+            for (rank, entry) in boards.most_submitted_charts.iter().enumerate() {
+                stmt.execute(params![
+                    recorded_at,
+                    CHARTS,
+                    rank + 1,
+                    entry.agent_symbol,
+                    entry.chart_count
+                ])?;
+            }
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Recorded samples matching the query, oldest first.
+    pub fn history(&self, query: &HistoryQuery) -> Result<Vec<HistorySample>, anyhow::Error> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare_cached(
+            "SELECT recorded_at, board, agent_symbol, value FROM leaderboard_history
+            WHERE (?1 IS NULL OR recorded_at >= ?1) AND (?2 IS NULL OR recorded_at < ?2)
+            ORDER BY recorded_at, board, rank",
+        )?;
+        let rows = stmt.query_map(params![query.since, query.until], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, i64>(3)?,
+            ))
+        })?;
+        let mut boards: HashMap<i64, (Vec<_>, Vec<_>)> = HashMap::new();
+        for row in rows {
+            let (recorded_at, board, agent_symbol, value) = row?;
+            let (credits, charts) = boards.entry(recorded_at).or_default();
+            if board == CREDITS {
+                credits.push(LeaderboardAgentCredits {
+                    agent_symbol,
+                    credits: value,
+                });
+            } else {
+                charts.push(LeaderboardAgentCharts {
+                    agent_symbol,
+                    chart_count: value.try_into()?,
+                });
+            }
+        }
+
+        let mut stmt = conn.prepare_cached(
+            "SELECT recorded_at, agents, ships, systems, waypoints, agent_symbol, credits, ship_count,
+                credits_rank
+            FROM status_history
+            WHERE (?1 IS NULL OR recorded_at >= ?1) AND (?2 IS NULL OR recorded_at < ?2)
+            ORDER BY recorded_at",
+        )?;
+        let rows = stmt.query_map(params![query.since, query.until], |row| {
+            Ok(HistorySample {
+                recorded_at: row.get(0)?,
+                agents: row.get(1)?,
+                ships: row.get(2)?,
+                systems: row.get(3)?,
+                waypoints: row.get(4)?,
+                agent_symbol: row.get(5)?,
+                credits: row.get(6)?,
+                ship_count: row.get(7)?,
+                credits_rank: row.get(8)?,
+                charts_rank: None,
+                most_credits: Vec::new(),
+                most_submitted_charts: Vec::new(),
+            })
+        })?;
+
+        rows.map(|sample| {
+            let mut sample = sample?;
+            if let Some((credits, charts)) = boards.remove(&sample.recorded_at) {
+                sample.most_credits = credits;
+                sample.most_submitted_charts = charts;
+            }
+            if let Some(symbol) = &sample.agent_symbol {
+                sample.credits_rank = sample
+                    .credits_rank
+                    .or_else(|| rank(sample.most_credits.iter().map(|e| &e.agent_symbol), symbol));
+                sample.charts_rank = rank(
+                    sample.most_submitted_charts.iter().map(|e| &e.agent_symbol),
+                    symbol,
+                );
+            }
+            Ok(sample)
+        })
+        .collect()
+    }
+}
+
+fn rank<'a>(mut board: impl Iterator<Item = &'a String>, symbol: &str) -> Option<u32> {
+    board.position(|s| s == symbol).map(|i| i as u32 + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(credits: &[(&str, i64)]) -> ApiStatus {
+        let most_credits: Vec<_> = credits
+            .iter()
+            .map(|(symbol, credits)| serde_json::json!({ "agentSymbol": symbol, "credits": credits }))
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "status": "SpaceTraders is currently online",
+            "version": "v2.3.0",
+            "resetDate": "2025-01-01",
+            "description": "",
+            "stats": { "agents": 100, "ships": 500, "systems": 10, "waypoints": 200 },
+            "leaderboards": {
+                "mostCredits": most_credits,
+                "mostSubmittedCharts": [{ "agentSymbol": "OTHER", "chartCount": 12 }]
+            },
+            "serverResets": { "next": "2025-01-15", "frequency": "fortnightly" },
+            "announcements": [],
+            "links": []
+        }))
+        .unwrap()
+    }
+
+    fn agent(credits: i64) -> Agent {
+        serde_json::from_value(serde_json::json!({
+            "accountId": "account",
+            "symbol": "CAT",
+            "headquarters": "X1-A-1",
+            "credits": credits,
+            "startingFaction": "COSMIC",
+            "shipCount": 2
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn ranks_our_agent_over_time() {
+        let store = Store::open_in_memory().unwrap();
+        store
+            .record_history(
+                1000,
+                &status(&[("OTHER", 500_000)]),
+                Some(&agent(100_000)),
+                None,
+            )
+            .unwrap();
+        store
+            .record_history(
+                1001,
+                &status(&[("OTHER", 600_000), ("CAT", 200_000)]),
+                Some(&agent(200_000)),
+                None,
+            )
+            .unwrap();
+        store
+            .record_history(1002, &status(&[]), Some(&agent(300_000)), Some(17))
+            .unwrap();
+        store
+            .record_history(1003, &status(&[]), None, None)
+            .unwrap();
+
+        let history = store.history(&HistoryQuery::default()).unwrap();
+
+        assert_eq!(history.len(), 4);
+        assert_eq!(history[0].credits, Some(100_000));
+        assert_eq!(history[0].credits_rank, None);
+        assert_eq!(history[1].credits_rank, Some(2));
+        assert_eq!(history[1].most_credits[0].credits, 600_000);
+        assert_eq!(history[1].most_submitted_charts[0].chart_count, 12);
+        assert_eq!(history[2].credits_rank, Some(17));
+        assert_eq!(history[3].agent_symbol, None);
+        assert_eq!(history[3].agents, 100);
+    }
+}
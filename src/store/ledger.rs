@@ -0,0 +1,466 @@
+use std::collections::{BTreeMap, HashMap};
+
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::{from_variant_name, now_millis, variant_name, Store};
+use crate::{
+    client,
+    model::{ApiResponseData, MarketTransaction, TradeSymbol, TransactionType},
+};
+
+/// What a ledger entry was for.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LedgerKind {
+    /// Goods bought at a market.
+    Purchase,
+    /// Goods sold at a market.
+    Sale,
+    Refuel,
+    /// Antimatter consumed by a jump.
+    Jump,
+    ShipPurchase,
+    /// Installing or removing a mount.
+    Modification,
+    Repair,
+    Scrap,
+    ContractAccepted,
+    ContractFulfilled,
+}
+
+impl LedgerKind {
+    /// Whether the credits were spent on fuel, in the broadest sense.
+    pub fn is_fuel(self) -> bool {
+        matches!(self, LedgerKind::Refuel | LedgerKind::Jump)
+    }
+}
+
+/// A single change to our credits.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerEntry {
+    /// When the entry was recorded, in milliseconds since the Unix epoch.
+    pub recorded_at: i64,
+    /// The timestamp of the transaction as reported by the API, if there was one.
+    pub timestamp: Option<String>,
+    pub kind: LedgerKind,
+    pub ship_symbol: Option<String>,
+    pub contract_id: Option<String>,
+    pub waypoint_symbol: Option<String>,
+    pub trade_symbol: Option<TradeSymbol>,
+    pub units: Option<u64>,
+    /// Credits received, or negative if credits were spent.
+    pub credits: i64,
+    /// The task that made the request, if it was made on behalf of one.
+    pub task: Option<String>,
+}
+
+/// Filters for the ledger. All of them are optional.
+#[derive(Deserialize, Debug, Default, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct LedgerQuery {
+    /// Only entries at or after this time, in milliseconds since the Unix epoch.
+    pub since: Option<i64>,
+    /// Only entries before this time, in milliseconds since the Unix epoch.
+    pub until: Option<i64>,
+    /// Only entries of this ship.
+    pub ship: Option<String>,
+    /// Only entries recorded on behalf of this task.
+    pub task: Option<String>,
+}
+
+/// Profit and loss over a period of time.
+#[derive(Serialize, Debug, Clone, PartialEq, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Report {
+    /// The start of the period, in milliseconds since the Unix epoch.
+    pub since: i64,
+    /// The end of the period, in milliseconds since the Unix epoch.
+    pub until: i64,
+    pub income: i64,
+    pub expenses: i64,
+    pub net: i64,
+    /// Credits spent on refueling and jumping, included in `expenses`.
+    pub fuel: i64,
+    pub credits_per_hour: f64,
+    pub ships: Vec<ShipReport>,
+    pub routes: Vec<RouteReport>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ShipReport {
+    pub ship_symbol: String,
+    /// When the first entry of the ship in the period was recorded,
+    /// in milliseconds since the Unix epoch.
+    pub first_recorded_at: i64,
+    pub income: i64,
+    pub expenses: i64,
+    pub net: i64,
+    pub fuel: i64,
+    pub transactions: u64,
+}
+
+/// Trading profit of one good bought at one market and sold at another.
+///
+/// Sales are attributed to the last purchase of the same good by the same ship.
+/// Sales whose purchase happened before the period are left out.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteReport {
+    pub trade_symbol: TradeSymbol,
+    pub from: String,
+    pub to: String,
+    pub units: u64,
+    /// What the sold units cost when they were bought.
+    pub cost: i64,
+    pub revenue: i64,
+    pub profit: i64,
+    pub sales: u64,
+}
+
+impl Store {
+    /// Record the transactions contained in a response, if any.
+    /// Previews of repairs and scraps are not transactions and are ignored.
+    pub fn record_transactions(&self, data: &ApiResponseData) -> Result<(), anyhow::Error> {
+        let task = client::current_task();
+        let recorded_at = now_millis();
+        let entry = |kind, credits| LedgerEntry {
+            recorded_at,
+            timestamp: None,
+            kind,
+            ship_symbol: None,
+            contract_id: None,
+            waypoint_symbol: None,
+            trade_symbol: None,
+            units: None,
+            credits,
+            task: task.clone(),
+        };
+        let market = |kind, t: &MarketTransaction| LedgerEntry {
+            timestamp: Some(t.timestamp.clone()),
+            ship_symbol: Some(t.ship_symbol.clone()),
+            waypoint_symbol: Some(t.waypoint_symbol.clone()),
+            trade_symbol: Some(t.trade_symbol),
+            units: Some(t.units),
+            ..entry(
+                kind,
+                match t.transaction_type {
+                    TransactionType::Purchase => -(t.total_price as i64),
+                    TransactionType::Sell => t.total_price as i64,
+                },
+            )
+        };
+
+        let entry = match data {
+            ApiResponseData::MarketTransaction { transaction, .. } => {
+                let kind = match transaction.transaction_type {
+                    TransactionType::Purchase => LedgerKind::Purchase,
+                    TransactionType::Sell => LedgerKind::Sale,
+                };
+                market(kind, transaction)
+            }
+            ApiResponseData::RefuelShip { transaction, .. } => {
+                market(LedgerKind::Refuel, transaction)
+            }
+            ApiResponseData::JumpShip { transaction, .. } => market(LedgerKind::Jump, transaction),
+            ApiResponseData::ShipPurchase { transaction, .. } => LedgerEntry {
+                timestamp: Some(transaction.timestamp.clone()),
+                ship_symbol: Some(transaction.ship_symbol.clone()),
+                waypoint_symbol: Some(transaction.waypoint_symbol.clone()),
+                ..entry(LedgerKind::ShipPurchase, -(transaction.price as i64))
+            },
+            ApiResponseData::ModifyMount { transaction, .. } => LedgerEntry {
+                timestamp: Some(transaction.timestamp.clone()),
+                ship_symbol: Some(transaction.ship_symbol.clone()),
+                waypoint_symbol: Some(transaction.waypoint_symbol.clone()),
+                trade_symbol: Some(transaction.trade_symbol),
+                ..entry(LedgerKind::Modification, -(transaction.total_price as i64))
+            },
+            ApiResponseData::RepairShip { transaction, .. } => LedgerEntry {
+                timestamp: Some(transaction.timestamp.clone()),
+                ship_symbol: Some(transaction.ship_symbol.clone()),
+                waypoint_symbol: Some(transaction.waypoint_symbol.clone()),
+                ..entry(LedgerKind::Repair, -(transaction.total_price as i64))
+            },
+            ApiResponseData::ScrapShip { transaction, .. } => LedgerEntry {
+                timestamp: Some(transaction.timestamp.clone()),
+                ship_symbol: Some(transaction.ship_symbol.clone()),
+                waypoint_symbol: Some(transaction.waypoint_symbol.clone()),
+                ..entry(LedgerKind::Scrap, transaction.total_price as i64)
+            },
+            // Accepting and fulfilling a contract return the agent, delivering cargo does not.
+            ApiResponseData::UpdateContract {
+                agent: Some(_),
+                contract,
+                ..
+            } => {
+                let (kind, credits) = if contract.fulfilled {
+                    (
+                        LedgerKind::ContractFulfilled,
+                        contract.terms.payment.on_fulfilled,
+                    )
+                } else {
+                    (
+                        LedgerKind::ContractAccepted,
+                        contract.terms.payment.on_accepted,
+                    )
+                };
+                LedgerEntry {
+                    contract_id: Some(contract.id.clone()),
+                    ..entry(kind, credits as i64)
+                }
+            }
+            _ => return Ok(()),
+        };
+
+        self.insert_ledger_entry(&entry)
+    }
+
+    fn insert_ledger_entry(&self, entry: &LedgerEntry) -> Result<(), anyhow::Error> {
+        self.conn.lock().unwrap().execute(
+            "INSERT INTO ledger (
+                recorded_at, timestamp, kind, ship_symbol, contract_id,
+                waypoint_symbol, trade_symbol, units, credits, task
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                entry.recorded_at,
+                entry.timestamp,
+                variant_name(&entry.kind)?,
+                entry.ship_symbol,
+                entry.contract_id,
+                entry.waypoint_symbol,
+                entry.trade_symbol.as_ref().map(variant_name).transpose()?,
+                entry.units,
+                entry.credits,
+                entry.task,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Ledger entries matching the query, oldest first.
+    pub fn ledger(&self, query: &LedgerQuery) -> Result<Vec<LedgerEntry>, anyhow::Error> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare_cached(
+            "SELECT recorded_at, timestamp, kind, ship_symbol, contract_id,
+                waypoint_symbol, trade_symbol, units, credits, task
+            FROM ledger
+            WHERE (?1 IS NULL OR recorded_at >= ?1)
+                AND (?2 IS NULL OR recorded_at < ?2)
+                AND (?3 IS NULL OR ship_symbol = ?3)
+                AND (?4 IS NULL OR task = ?4)
+            ORDER BY recorded_at, id",
+        )?;
+        let rows = stmt.query_map(
+            params![query.since, query.until, query.ship, query.task],
+            |row| {
+                Ok((
+                    LedgerEntry {
+                        recorded_at: row.get(0)?,
+                        timestamp: row.get(1)?,
+                        kind: LedgerKind::Purchase,
+                        ship_symbol: row.get(3)?,
+                        contract_id: row.get(4)?,
+                        waypoint_symbol: row.get(5)?,
+                        trade_symbol: None,
+                        units: row.get(7)?,
+                        credits: row.get(8)?,
+                        task: row.get(9)?,
+                    },
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Option<String>>(6)?,
+                ))
+            },
+        )?;
+
+        rows.map(|row| {
+            let (entry, kind, trade_symbol) = row?;
+            Ok(LedgerEntry {
+                kind: from_variant_name(kind)?,
+                trade_symbol: trade_symbol.map(from_variant_name).transpose()?,
+                ..entry
+            })
+        })
+        .collect()
+    }
+
+    /// Profit and loss of the entries matching the query.
+    pub fn report(&self, query: &LedgerQuery) -> Result<Report, anyhow::Error> {
+        let entries = self.ledger(query)?;
+        let until = query.until.unwrap_or_else(now_millis);
+        let since = query
+            .since
+            .or_else(|| entries.first().map(|e| e.recorded_at))
+            .unwrap_or(until);
+
+        Ok(report(&entries, since, until))
+    }
+}
+
+fn report(entries: &[LedgerEntry], since: i64, until: i64) -> Report {
+    let mut ships: BTreeMap<&str, ShipReport> = BTreeMap::new();
+    let mut routes: HashMap<(TradeSymbol, &str, &str), RouteReport> = HashMap::new();
+    // The waypoint and price per unit of the last purchase of a good by a ship.
+    let mut bought: HashMap<(&str, TradeSymbol), (&str, i64)> = HashMap::new();
+    let (mut income, mut expenses, mut fuel) = (0, 0, 0);
+
+    for entry in entries {
+        let (entry_income, entry_expenses) = if entry.credits >= 0 {
+            (entry.credits, 0)
+        } else {
+            (0, -entry.credits)
+        };
+        let entry_fuel = if entry.kind.is_fuel() {
+            entry_expenses
+        } else {
+            0
+        };
+        income += entry_income;
+        expenses += entry_expenses;
+        fuel += entry_fuel;
+
+        let Some(ship) = entry.ship_symbol.as_deref() else {
+            continue;
+        };
+        let report = ships.entry(ship).or_insert_with(|| ShipReport {
+            ship_symbol: ship.to_string(),
+            first_recorded_at: entry.recorded_at,
+            income: 0,
+            expenses: 0,
+            net: 0,
+            fuel: 0,
+            transactions: 0,
+        });
+        report.income += entry_income;
+        report.expenses += entry_expenses;
+        report.net += entry.credits;
+        report.fuel += entry_fuel;
+        report.transactions += 1;
+
+        let (Some(good), Some(waypoint), Some(units)) = (
+            entry.trade_symbol,
+            entry.waypoint_symbol.as_deref(),
+            entry.units.filter(|&units| units > 0),
+        ) else {
+            continue;
+        };
+        match entry.kind {
+            LedgerKind::Purchase => {
+                bought.insert((ship, good), (waypoint, entry_expenses / units as i64));
+            }
+            LedgerKind::Sale => {
+                let Some(&(from, price)) = bought.get(&(ship, good)) else {
+                    continue;
+                };
+                let route = routes
+                    .entry((good, from, waypoint))
+                    .or_insert_with(|| RouteReport {
+                        trade_symbol: good,
+                        from: from.to_string(),
+                        to: waypoint.to_string(),
+                        units: 0,
+                        cost: 0,
+                        revenue: 0,
+                        profit: 0,
+                        sales: 0,
+                    });
+                let cost = price * units as i64;
+                route.units += units;
+                route.cost += cost;
+                route.revenue += entry_income;
+                route.profit += entry_income - cost;
+                route.sales += 1;
+            }
+            _ => {}
+        }
+    }
+
+    let net = income - expenses;
+    let hours = (until - since) as f64 / (60.0 * 60.0 * 1000.0);
+    let mut routes: Vec<_> = routes.into_values().collect();
+    routes.sort_by_key(|route| std::cmp::Reverse(route.profit));
+
+    Report {
+        since,
+        until,
+        income,
+        expenses,
+        net,
+        fuel,
+        credits_per_hour: if hours > 0.0 { net as f64 / hours } else { 0.0 },
+        ships: ships.into_values().collect(),
+        routes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(ship: &str, waypoint: &str, kind: &str, units: u64, price: u64) -> ApiResponseData {
+        serde_json::from_value(serde_json::json!({
+            "agent": {
+                "accountId": "account",
+                "symbol": "CATBRAINED",
+                "headquarters": "X1-A-1",
+                "credits": 100000,
+                "startingFaction": "COSMIC",
+                "shipCount": 2
+            },
+            "cargo": { "capacity": 40, "units": 0, "inventory": [] },
+            "transaction": {
+                "waypointSymbol": waypoint,
+                "shipSymbol": ship,
+                "tradeSymbol": "IRON_ORE",
+                "type": kind,
+                "units": units,
+                "pricePerUnit": price,
+                "totalPrice": units * price,
+                "timestamp": "2025-01-01T00:00:00.000Z"
+            }
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn reports_profit_per_ship_and_route() {
+        let store = Store::open_in_memory().unwrap();
+        client::in_task("trader", async {
+            store
+                .record_transactions(&trade("CAT-1", "X1-A-1", "PURCHASE", 10, 20))
+                .unwrap();
+            store
+                .record_transactions(&trade("CAT-1", "X1-A-2", "SELL", 10, 35))
+                .unwrap();
+        })
+        .await;
+        store
+            .record_transactions(&trade("CAT-2", "X1-A-2", "SELL", 5, 30))
+            .unwrap();
+
+        let entries = store.ledger(&LedgerQuery::default()).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].kind, LedgerKind::Purchase);
+        assert_eq!(entries[0].credits, -200);
+        assert_eq!(entries[0].task.as_deref(), Some("trader"));
+        assert_eq!(entries[2].task, None);
+
+        let report = report(&entries, 0, 60 * 60 * 1000);
+        assert_eq!(report.income, 350 + 150);
+        assert_eq!(report.expenses, 200);
+        assert_eq!(report.net, 300);
+        assert_eq!(report.credits_per_hour, 300.0);
+        assert_eq!(report.ships.len(), 2);
+        assert_eq!(report.ships[0].net, 150);
+        // CAT-2 never bought what it sold, so only CAT-1 made a route.
+        assert_eq!(report.routes.len(), 1);
+        assert_eq!(report.routes[0].from, "X1-A-1");
+        assert_eq!(report.routes[0].to, "X1-A-2");
+        assert_eq!(report.routes[0].profit, 150);
+    }
+}
@@ -0,0 +1,780 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Context;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::{event, instrument, Level};
+
+pub use history::{HistoryQuery, HistorySample};
+#[cfg(test)]
+pub use ledger::LedgerKind;
+pub use ledger::{LedgerEntry, LedgerQuery, Report, ShipReport};
+pub use prices::{PriceQuery, PriceRecord};
+pub use surveys::RankedSurvey;
+
+mod history;
+mod ledger;
+mod prices;
+mod surveys;
+
+use crate::{
+    client::Observer,
+    model::{Agent, ApiResponseData, Contract, Market, Ship, Shipyard, System, Waypoint},
+};
+
+/// Schema migrations, applied in order.
+/// The number of applied migrations is tracked in `PRAGMA user_version`,
+/// so existing entries must never be changed, only new ones appended.
+const MIGRATIONS: &[&str] = &[
+    // 1: Objects of the universe and our fleet, keyed by symbol.
+    "CREATE TABLE systems (
+        symbol TEXT PRIMARY KEY NOT NULL,
+        data TEXT NOT NULL,
+        fetched_at INTEGER NOT NULL
+    );
+    CREATE TABLE waypoints (
+        symbol TEXT PRIMARY KEY NOT NULL,
+        data TEXT NOT NULL,
+        fetched_at INTEGER NOT NULL
+    );
+    CREATE INDEX waypoints_system ON waypoints (json_extract(data, '$.systemSymbol'));
+    CREATE TABLE markets (
+        symbol TEXT PRIMARY KEY NOT NULL,
+        data TEXT NOT NULL,
+        fetched_at INTEGER NOT NULL
+    );
+    CREATE TABLE shipyards (
+        symbol TEXT PRIMARY KEY NOT NULL,
+        data TEXT NOT NULL,
+        fetched_at INTEGER NOT NULL
+    );
+    CREATE TABLE ships (
+        symbol TEXT PRIMARY KEY NOT NULL,
+        data TEXT NOT NULL,
+        fetched_at INTEGER NOT NULL
+    );",
+    // 2: Trade goods of every market snapshot we observed.
+    "CREATE TABLE market_prices (
+        waypoint_symbol TEXT NOT NULL,
+        trade_symbol TEXT NOT NULL,
+        recorded_at INTEGER NOT NULL,
+        good_type TEXT NOT NULL,
+        supply TEXT NOT NULL,
+        activity TEXT,
+        purchase_price INTEGER NOT NULL,
+        sell_price INTEGER NOT NULL,
+        trade_volume INTEGER NOT NULL
+    );
+    CREATE INDEX market_prices_waypoint ON market_prices (waypoint_symbol, trade_symbol, recorded_at);
+    CREATE INDEX market_prices_good ON market_prices (trade_symbol, recorded_at);",
+    // 3: Every transaction that changed our credits.
+    "CREATE TABLE ledger (
+        id INTEGER PRIMARY KEY,
+        recorded_at INTEGER NOT NULL,
+        timestamp TEXT,
+        kind TEXT NOT NULL,
+        ship_symbol TEXT,
+        contract_id TEXT,
+        waypoint_symbol TEXT,
+        trade_symbol TEXT,
+        units INTEGER,
+        credits INTEGER NOT NULL,
+        task TEXT
+    );
+    CREATE INDEX ledger_recorded_at ON ledger (recorded_at);",
+    // 4: Surveys that can still be used for extraction.
+    "CREATE TABLE surveys (
+        signature TEXT PRIMARY KEY NOT NULL,
+        waypoint_symbol TEXT NOT NULL,
+        data TEXT NOT NULL,
+        expires_at INTEGER NOT NULL
+    );
+    CREATE INDEX surveys_waypoint ON surveys (waypoint_symbol);",
+    // 5: Progress of long running jobs, so they can resume after a restart.
+    "CREATE TABLE job_state (
+        name TEXT PRIMARY KEY NOT NULL,
+        state TEXT NOT NULL
+    );
+    CREATE TABLE crawled_systems (
+        symbol TEXT PRIMARY KEY NOT NULL,
+        crawled_at INTEGER NOT NULL
+    );",
+    // 6: Agents we registered during this reset, and their tokens.
+    "CREATE TABLE agents (
+        symbol TEXT PRIMARY KEY NOT NULL,
+        token TEXT NOT NULL,
+        registered_at INTEGER NOT NULL
+    );",
+    // 7: Our agent and contracts, stored like the universe above.
+    "CREATE TABLE agent_details (
+        symbol TEXT PRIMARY KEY NOT NULL,
+        data TEXT NOT NULL,
+        fetched_at INTEGER NOT NULL
+    );
+    CREATE TABLE contracts (
+        symbol TEXT PRIMARY KEY NOT NULL,
+        data TEXT NOT NULL,
+        fetched_at INTEGER NOT NULL
+    );",
+    // 8: Periodic samples of the game stats, the leaderboards and our agent.
+    "CREATE TABLE status_history (
+        recorded_at INTEGER PRIMARY KEY NOT NULL,
+        agents INTEGER NOT NULL,
+        ships INTEGER NOT NULL,
+        systems INTEGER NOT NULL,
+        waypoints INTEGER NOT NULL,
+        agent_symbol TEXT,
+        credits INTEGER,
+        ship_count INTEGER,
+        credits_rank INTEGER
+    );
+    CREATE TABLE leaderboard_history (
+        recorded_at INTEGER NOT NULL,
+        board TEXT NOT NULL,
+        rank INTEGER NOT NULL,
+        agent_symbol TEXT NOT NULL,
+        value INTEGER NOT NULL,
+        PRIMARY KEY (recorded_at, board, rank)
+    );",
+];
+
+/// A model type that is stored in its own table, keyed by its symbol.
+pub trait Record: Serialize + DeserializeOwned {
+    const TABLE: &'static str;
+
+    fn symbol(&self) -> &str;
+}
+
+impl Record for System {
+    const TABLE: &'static str = "systems";
+
+    fn symbol(&self) -> &str {
+        &self.symbol.0
+    }
+}
+
+impl Record for Waypoint {
+    const TABLE: &'static str = "waypoints";
+
+    fn symbol(&self) -> &str {
+        &self.symbol
+    }
+}
+
+impl Record for Market {
+    const TABLE: &'static str = "markets";
+
+    fn symbol(&self) -> &str {
+        &self.symbol
+    }
+}
+
+impl Record for Shipyard {
+    const TABLE: &'static str = "shipyards";
+
+    fn symbol(&self) -> &str {
+        &self.symbol
+    }
+}
+
+impl Record for Agent {
+    const TABLE: &'static str = "agent_details";
+
+    fn symbol(&self) -> &str {
+        &self.symbol
+    }
+}
+
+/// Contracts have no symbol, so they are keyed by their ID.
+impl Record for Contract {
+    const TABLE: &'static str = "contracts";
+
+    fn symbol(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Record for Ship {
+    const TABLE: &'static str = "ships";
+
+    fn symbol(&self) -> &str {
+        &self.symbol
+    }
+}
+
+/// A stored object, together with the time it was fetched from the API.
+#[derive(Debug, Clone)]
+pub struct Stored<T> {
+    pub value: T,
+    pub fetched_at: SystemTime,
+}
+
+impl<T> Stored<T> {
+    /// The object, if it was fetched within `max_age`.
+    pub fn fresh(self, max_age: Duration) -> Option<T> {
+        let age = SystemTime::now()
+            .duration_since(self.fetched_at)
+            .unwrap_or_default();
+
+        (age <= max_age).then_some(self.value)
+    }
+}
+
+/// Persistent storage of everything catfleet learns about the universe and our fleet,
+/// backed by a SQLite database.
+///
+/// Everything in the store belongs to the current reset of the universe.
+/// When the universe is reset, the database is archived and a new one is started.
+#[derive(Debug)]
+pub struct Store {
+    conn: Mutex<Connection>,
+    /// The database file, or `None` if the database is in memory.
+    path: Option<PathBuf>,
+}
+
+impl Store {
+    /// Open the database at `path`, creating it if necessary,
+    /// and bring its schema up to date.
+    #[instrument(level = Level::DEBUG)]
+    pub fn open(path: &Path) -> Result<Self, anyhow::Error> {
+        Ok(Self {
+            conn: Mutex::new(open_file(path)?),
+            path: Some(path.to_path_buf()),
+        })
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self, anyhow::Error> {
+        let mut conn = Connection::open_in_memory()?;
+        migrate(&mut conn)?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+            path: None,
+        })
+    }
+
+    /// Move the database aside as `<name>-<label>.<extension>` and start over with an empty one.
+    /// Returns where the old database was moved to.
+    #[instrument(level = Level::INFO, skip(self))]
+    pub fn archive(&self, label: &str) -> Result<Option<PathBuf>, anyhow::Error> {
+        let mut conn = self.conn.lock().unwrap();
+        let Some(path) = &self.path else {
+            let mut fresh = Connection::open_in_memory()?;
+            migrate(&mut fresh)?;
+            *conn = fresh;
+            return Ok(None);
+        };
+
+        conn.pragma_update(None, "wal_checkpoint", "TRUNCATE")?;
+        let old = std::mem::replace(&mut *conn, Connection::open_in_memory()?);
+        if let Err((old, err)) = old.close() {
+            *conn = old;
+            return Err(err.into());
+        }
+
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let mut name = format!("{stem}-{label}");
+        if let Some(extension) = path.extension() {
+            name = format!("{name}.{}", extension.to_string_lossy());
+        }
+        let archived = path.with_file_name(name);
+        if let Err(err) = std::fs::rename(path, &archived) {
+            // Keep going with the database that could not be moved.
+            *conn = open_file(path)?;
+            return Err(err).with_context(|| {
+                format!("Moving `{}` to `{}`", path.display(), archived.display())
+            });
+        }
+        for suffix in ["-wal", "-shm"] {
+            let mut leftover = path.clone().into_os_string();
+            leftover.push(suffix);
+            let _ = std::fs::remove_file(leftover);
+        }
+
+        *conn = open_file(path)?;
+        event!(Level::INFO, "Archived database to `{}`", archived.display());
+        Ok(Some(archived))
+    }
+
+    /// Insert or replace an object, marking it as fetched just now.
+    pub fn put<T: Record>(&self, value: &T) -> Result<(), anyhow::Error> {
+        let data = serde_json::to_string(value)?;
+
+        self.conn.lock().unwrap().execute(
+            &format!(
+                "INSERT OR REPLACE INTO {} (symbol, data, fetched_at) VALUES (?1, ?2, ?3)",
+                T::TABLE
+            ),
+            params![value.symbol(), data, now_millis()],
+        )?;
+
+        Ok(())
+    }
+
+    /// Insert or replace many objects at once, in a single transaction.
+    pub fn put_all<T: Record>(&self, values: &[T]) -> Result<(), anyhow::Error> {
+        self.put_all_fetched_at(values, now_millis())
+    }
+
+    /// Like [`Store::put_all`], but for objects that were fetched at an earlier time,
+    /// in milliseconds since the Unix epoch.
+    pub fn put_all_fetched_at<T: Record>(
+        &self,
+        values: &[T],
+        fetched_at: i64,
+    ) -> Result<(), anyhow::Error> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(&format!(
+                "INSERT OR REPLACE INTO {} (symbol, data, fetched_at) VALUES (?1, ?2, ?3)",
+                T::TABLE
+            ))?;
+            for value in values {
+                stmt.execute(params![
+                    value.symbol(),
+                    serde_json::to_string(value)?,
+                    fetched_at
+                ])?;
+            }
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// The number of stored objects of a type.
+    pub fn count<T: Record>(&self) -> Result<u64, anyhow::Error> {
+        Ok(self.conn.lock().unwrap().query_row(
+            &format!("SELECT COUNT(*) FROM {}", T::TABLE),
+            [],
+            |row| row.get(0),
+        )?)
+    }
+
+    pub fn get<T: Record>(&self, symbol: &str) -> Result<Option<Stored<T>>, anyhow::Error> {
+        let row = self
+            .conn
+            .lock()
+            .unwrap()
+            .query_row(
+                &format!(
+                    "SELECT data, fetched_at FROM {} WHERE symbol = ?1",
+                    T::TABLE
+                ),
+                params![symbol],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)),
+            )
+            .optional()?;
+
+        row.map(|(data, fetched_at)| stored(&data, fetched_at))
+            .transpose()
+    }
+
+    /// All stored objects of a type, ordered by symbol.
+    pub fn all<T: Record>(&self) -> Result<Vec<Stored<T>>, anyhow::Error> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT data, fetched_at FROM {} ORDER BY symbol",
+            T::TABLE
+        ))?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })?;
+
+        rows.map(|row| {
+            let (data, fetched_at) = row?;
+            stored(&data, fetched_at)
+        })
+        .collect()
+    }
+
+    /// Modify a stored object in place, keeping the time it was fetched.
+    /// Does nothing if the object is not stored.
+    pub fn update<T: Record>(
+        &self,
+        symbol: &str,
+        f: impl FnOnce(&mut T),
+    ) -> Result<(), anyhow::Error> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        let data: Option<String> = tx
+            .query_row(
+                &format!("SELECT data FROM {} WHERE symbol = ?1", T::TABLE),
+                params![symbol],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if let Some(data) = data {
+            let mut value: T = serde_json::from_str(&data)?;
+            f(&mut value);
+            tx.execute(
+                &format!("UPDATE {} SET data = ?2 WHERE symbol = ?1", T::TABLE),
+                params![symbol, serde_json::to_string(&value)?],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn remove<T: Record>(&self, symbol: &str) -> Result<(), anyhow::Error> {
+        self.conn.lock().unwrap().execute(
+            &format!("DELETE FROM {} WHERE symbol = ?1", T::TABLE),
+            params![symbol],
+        )?;
+
+        Ok(())
+    }
+
+    /// All waypoints of a system that we know of.
+    pub fn waypoints_in_system(
+        &self,
+        system_symbol: &str,
+    ) -> Result<Vec<Stored<Waypoint>>, anyhow::Error> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare_cached(
+            "SELECT data, fetched_at FROM waypoints
+            WHERE json_extract(data, '$.systemSymbol') = ?1
+            ORDER BY symbol",
+        )?;
+        let rows = stmt.query_map(params![system_symbol], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })?;
+
+        rows.map(|row| {
+            let (data, fetched_at) = row?;
+            stored(&data, fetched_at)
+        })
+        .collect()
+    }
+
+    /// The saved state of a job, if it saved any.
+    pub fn job_state<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>, anyhow::Error> {
+        let state: Option<String> = self
+            .conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT state FROM job_state WHERE name = ?1",
+                params![name],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        Ok(state
+            .map(|state| serde_json::from_str(&state))
+            .transpose()?)
+    }
+
+    pub fn set_job_state<T: Serialize>(&self, name: &str, state: &T) -> Result<(), anyhow::Error> {
+        self.conn.lock().unwrap().execute(
+            "INSERT OR REPLACE INTO job_state (name, state) VALUES (?1, ?2)",
+            params![name, serde_json::to_string(state)?],
+        )?;
+
+        Ok(())
+    }
+
+    /// The token of the agent registered most recently during this reset.
+    pub fn agent_token(&self) -> Result<Option<String>, anyhow::Error> {
+        Ok(self
+            .conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT token FROM agents ORDER BY registered_at DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    pub fn add_agent(&self, symbol: &str, token: &str) -> Result<(), anyhow::Error> {
+        self.conn.lock().unwrap().execute(
+            "INSERT OR REPLACE INTO agents (symbol, token, registered_at) VALUES (?1, ?2, ?3)",
+            params![symbol, token, now_millis()],
+        )?;
+
+        Ok(())
+    }
+
+    /// Systems whose waypoints have not been crawled yet.
+    pub fn uncrawled_systems(&self) -> Result<Vec<System>, anyhow::Error> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare_cached(
+            "SELECT data FROM systems
+            WHERE symbol NOT IN (SELECT symbol FROM crawled_systems)
+            ORDER BY symbol",
+        )?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+
+        rows.map(|data| Ok(serde_json::from_str(&data?)?)).collect()
+    }
+
+    pub fn mark_system_crawled(&self, symbol: &str) -> Result<(), anyhow::Error> {
+        self.conn.lock().unwrap().execute(
+            "INSERT OR REPLACE INTO crawled_systems (symbol, crawled_at) VALUES (?1, ?2)",
+            params![symbol, now_millis()],
+        )?;
+
+        Ok(())
+    }
+
+    /// Write all pending changes to the main database file.
+    /// Called on shutdown, so that the database can be copied around on its own.
+    pub fn checkpoint(&self) -> Result<(), anyhow::Error> {
+        self.conn
+            .lock()
+            .unwrap()
+            .pragma_update(None, "wal_checkpoint", "TRUNCATE")?;
+
+        Ok(())
+    }
+
+    fn observe_inner(
+        &self,
+        ship: Option<&str>,
+        data: &ApiResponseData,
+    ) -> Result<(), anyhow::Error> {
+        self.record_transactions(data)?;
+        self.record_agent_and_contracts(data)?;
+
+        match data {
+            ApiResponseData::GetSystem(system) => self.put(system)?,
+            ApiResponseData::ListSystems(systems) => self.put_all(systems)?,
+            ApiResponseData::GetWaypoint(waypoint)
+            | ApiResponseData::CreateChart { waypoint, .. } => self.put(waypoint)?,
+            ApiResponseData::ListWaypoints(waypoints) => self.put_all(waypoints)?,
+            ApiResponseData::GetMarket(market) => {
+                self.put(market)?;
+                self.record_prices(market)?;
+            }
+            ApiResponseData::GetShipyard(shipyard) => self.put(shipyard)?,
+            ApiResponseData::CreateSurvey { surveys, .. } => {
+                for survey in surveys {
+                    self.put_survey(survey)?;
+                }
+            }
+            ApiResponseData::GetShip(s)
+            | ApiResponseData::ShipPurchase { ship: s, .. }
+            | ApiResponseData::RepairShip { ship: s, .. } => self.put(s.as_ref())?,
+            ApiResponseData::ListShips(ships) => self.put_all(ships)?,
+            ApiResponseData::ScrapShip { .. } => {
+                if let Some(ship) = ship {
+                    self.remove::<Ship>(ship)?;
+                }
+            }
+            _ => {
+                if let Some(ship) = ship {
+                    self.update(ship, |s: &mut Ship| s.apply(data))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Store {
+    fn record_agent_and_contracts(&self, data: &ApiResponseData) -> Result<(), anyhow::Error> {
+        let agent = match data {
+            ApiResponseData::GetAgent(agent)
+            | ApiResponseData::ShipPurchase { agent, .. }
+            | ApiResponseData::MarketTransaction { agent, .. }
+            | ApiResponseData::RefuelShip { agent, .. }
+            | ApiResponseData::JumpShip { agent, .. }
+            | ApiResponseData::ModifyMount { agent, .. }
+            | ApiResponseData::ScrapShip { agent, .. }
+            | ApiResponseData::RepairShip { agent, .. }
+            | ApiResponseData::UpdateContract {
+                agent: Some(agent), ..
+            } => Some(agent),
+            ApiResponseData::RegisterAgent(registered) => Some(&registered.agent),
+            _ => None,
+        };
+        // The account ID is only included on our own agent.
+        if let Some(agent) = agent.filter(|agent| agent.account_id.is_some()) {
+            self.put(agent)?;
+        }
+
+        match data {
+            ApiResponseData::ListContracts(contracts) => self.put_all(contracts)?,
+            ApiResponseData::GetContract(contract)
+            | ApiResponseData::NegotiateContract { contract }
+            | ApiResponseData::UpdateContract { contract, .. } => self.put(contract)?,
+            ApiResponseData::RegisterAgent(registered) => {
+                self.put(&registered.contract)?;
+                self.put(&registered.ship)?;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+impl Observer for Store {
+    fn observe(&self, ship: Option<&str>, data: &ApiResponseData) {
+        if let Err(err) = self.observe_inner(ship, data) {
+            event!(Level::WARN, "Failed to store response: {err:?}");
+        }
+    }
+
+    fn survey_unusable(&self, signature: &str) {
+        if let Err(err) = self.remove_survey(signature) {
+            event!(Level::WARN, "Failed to remove survey: {err:?}");
+        }
+    }
+}
+
+fn open_file(path: &Path) -> Result<Connection, anyhow::Error> {
+    let mut conn =
+        Connection::open(path).with_context(|| format!("Opening database `{}`", path.display()))?;
+    conn.pragma_update(None, "journal_mode", "WAL")?;
+    conn.pragma_update(None, "synchronous", "NORMAL")?;
+    migrate(&mut conn)?;
+
+    Ok(conn)
+}
+
+fn migrate(conn: &mut Connection) -> Result<(), anyhow::Error> {
+    let version: usize = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+
+    for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        event!(Level::INFO, "Applying database migration {}", i + 1);
+        let tx = conn.transaction()?;
+        tx.execute_batch(migration)
+            .with_context(|| format!("Applying migration {}", i + 1))?;
+        tx.pragma_update(None, "user_version", i + 1)?;
+        tx.commit()?;
+    }
+
+    Ok(())
+}
+
+fn stored<T: DeserializeOwned>(data: &str, fetched_at: i64) -> Result<Stored<T>, anyhow::Error> {
+    Ok(Stored {
+        value: serde_json::from_str(data)?,
+        fetched_at: UNIX_EPOCH + Duration::from_millis(fetched_at as u64),
+    })
+}
+
+/// The name of a unit enum variant as it is serialized, e.g. `IRON_ORE`.
+fn variant_name<T: Serialize>(value: &T) -> Result<String, anyhow::Error> {
+    match serde_json::to_value(value)? {
+        serde_json::Value::String(name) => Ok(name),
+        other => anyhow::bail!("Expected a unit variant, got `{other}`"),
+    }
+}
+
+fn from_variant_name<T: DeserializeOwned>(name: String) -> Result<T, anyhow::Error> {
+    Ok(serde_json::from_value(serde_json::Value::String(name))?)
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn waypoint(symbol: &str, system: &str) -> Waypoint {
+        serde_json::from_value(serde_json::json!({
+            "symbol": symbol,
+            "type": "PLANET",
+            "systemSymbol": system,
+            "x": 0,
+            "y": 0,
+            "orbitals": [],
+            "traits": [],
+            "isUnderConstruction": false
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn migrations_are_applied_once() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn).unwrap();
+        migrate(&mut conn).unwrap();
+
+        let version: usize = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, MIGRATIONS.len());
+    }
+
+    #[test]
+    fn stores_waypoints_by_system() {
+        let store = Store::open_in_memory().unwrap();
+        store.put(&waypoint("X1-A-1", "X1-A")).unwrap();
+        store.put(&waypoint("X1-A-2", "X1-A")).unwrap();
+        store.put(&waypoint("X1-B-1", "X1-B")).unwrap();
+
+        let stored = store.get::<Waypoint>("X1-A-2").unwrap().unwrap();
+        assert_eq!(stored.value.symbol, "X1-A-2");
+        assert!(stored.fresh(Duration::from_secs(60)).is_some());
+
+        let in_system = store.waypoints_in_system("X1-A").unwrap();
+        assert_eq!(in_system.len(), 2);
+
+        store.remove::<Waypoint>("X1-A-1").unwrap();
+        assert!(store.get::<Waypoint>("X1-A-1").unwrap().is_none());
+    }
+
+    #[test]
+    fn archives_the_database_and_starts_over() {
+        let dir =
+            std::env::temp_dir().join(format!("catfleet-test-archive-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("catfleet.db");
+        let store = Store::open(&path).unwrap();
+        store.put(&waypoint("X1-A-1", "X1-A")).unwrap();
+        store.add_agent("CATBRAINED", "secret").unwrap();
+
+        let archived = store.archive("2025-01-05").unwrap().unwrap();
+
+        assert_eq!(archived, dir.join("catfleet-2025-01-05.db"));
+        assert!(store.get::<Waypoint>("X1-A-1").unwrap().is_none());
+        assert_eq!(store.agent_token().unwrap(), None);
+        let old = Store::open(&archived).unwrap();
+        assert!(old.get::<Waypoint>("X1-A-1").unwrap().is_some());
+        assert_eq!(old.agent_token().unwrap().as_deref(), Some("secret"));
+
+        drop((store, old));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn keeps_the_database_when_archiving_fails() {
+        let dir = std::env::temp_dir().join(format!(
+            "catfleet-test-archive-fails-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("catfleet.db");
+        let store = Store::open(&path).unwrap();
+        store.put(&waypoint("X1-A-1", "X1-A")).unwrap();
+        // A non-empty directory in the way makes the rename fail.
+        std::fs::create_dir_all(dir.join("catfleet-2025-01-05.db").join("taken")).unwrap();
+
+        assert!(store.archive("2025-01-05").is_err());
+
+        assert!(store.get::<Waypoint>("X1-A-1").unwrap().is_some());
+        store.put(&waypoint("X1-A-2", "X1-A")).unwrap();
+
+        drop(store);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
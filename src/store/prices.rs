@@ -0,0 +1,282 @@
+use std::collections::HashMap;
+
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::{from_variant_name, now_millis, variant_name, Store};
+use crate::model::{ActivityLevel, Market, SupplyLevel, TradeGoodType, TradeSymbol};
+
+/// How many price records are returned if the query does not specify a limit.
+const DEFAULT_LIMIT: u32 = 1000;
+const MAX_LIMIT: u32 = 10_000;
+
+/// The state of one trade good at one market at a point in time.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceRecord {
+    pub waypoint_symbol: String,
+    pub trade_symbol: TradeSymbol,
+    /// When the market was observed, in milliseconds since the Unix epoch.
+    pub recorded_at: i64,
+    pub good_type: TradeGoodType,
+    pub supply: SupplyLevel,
+    pub activity: Option<ActivityLevel>,
+    pub purchase_price: u64,
+    pub sell_price: u64,
+    pub trade_volume: u64,
+}
+
+/// Filters for the price history. All of them are optional.
+#[derive(Deserialize, Debug, Default, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct PriceQuery {
+    /// Only records of the market at this waypoint.
+    pub waypoint: Option<String>,
+    /// Only records of this trade good.
+    pub good: Option<TradeSymbol>,
+    /// Only records at or after this time, in milliseconds since the Unix epoch.
+    pub since: Option<i64>,
+    /// Only records before this time, in milliseconds since the Unix epoch.
+    pub until: Option<i64>,
+    /// The maximum number of records to return, oldest first.
+    /// Defaults to 1000 and is capped at 10000.
+    pub limit: Option<u32>,
+}
+
+impl Store {
+    /// Record the trade goods of a market snapshot.
+    /// Markets only include trade goods while one of our ships is present,
+    /// so snapshots without them are ignored.
+    pub fn record_prices(&self, market: &Market) -> Result<(), anyhow::Error> {
+        let Some(goods) = &market.trade_goods else {
+            return Ok(());
+        };
+        let recorded_at = now_millis();
+
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO market_prices (
+                    waypoint_symbol, trade_symbol, recorded_at, good_type, supply,
+                    activity, purchase_price, sell_price, trade_volume
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            )?;
+            for good in goods {
+                stmt.execute(params![
+                    market.symbol,
+                    variant_name(&good.symbol)?,
+                    recorded_at,
+                    variant_name(&good.good_type)?,
+                    variant_name(&good.supply)?,
+                    good.activity.as_ref().map(variant_name).transpose()?,
+                    good.purchase_price,
+                    good.sell_price,
+                    good.trade_volume,
+                ])?;
+            }
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// The lowest price each good can currently be bought for at any market in a system,
+    /// going by the latest record of each market.
+    pub fn cheapest_purchase_prices(
+        &self,
+        system_symbol: &str,
+    ) -> Result<HashMap<TradeSymbol, u64>, anyhow::Error> {
+        self.latest_prices_by(system_symbol, "MIN", "purchase_price")
+    }
+
+    /// One price per good across the markets in a system, going by the latest record of
+    /// each market. `aggregate` (`MIN` or `MAX`) picks among the markets' `column` prices.
+    pub(super) fn latest_prices_by(
+        &self,
+        system_symbol: &str,
+        aggregate: &'static str,
+        column: &'static str,
+    ) -> Result<HashMap<TradeSymbol, u64>, anyhow::Error> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT trade_symbol, {aggregate}({column}) FROM market_prices AS m
+            WHERE waypoint_symbol LIKE ?1 || '-%'
+                AND recorded_at = (
+                    SELECT MAX(recorded_at) FROM market_prices
+                    WHERE waypoint_symbol = m.waypoint_symbol AND trade_symbol = m.trade_symbol
+                )
+            GROUP BY trade_symbol"
+        ))?;
+        let rows = stmt.query_map(params![system_symbol], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, u64>(1)?))
+        })?;
+
+        rows.map(|row| {
+            let (symbol, price) = row?;
+            Ok((from_variant_name(symbol)?, price))
+        })
+        .collect()
+    }
+
+    /// Recorded prices matching the query, oldest first.
+    pub fn price_history(&self, query: &PriceQuery) -> Result<Vec<PriceRecord>, anyhow::Error> {
+        let good = query.good.as_ref().map(variant_name).transpose()?;
+        let limit = query.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare_cached(
+            "SELECT waypoint_symbol, trade_symbol, recorded_at, good_type, supply,
+                activity, purchase_price, sell_price, trade_volume
+            FROM market_prices
+            WHERE (?1 IS NULL OR waypoint_symbol = ?1)
+                AND (?2 IS NULL OR trade_symbol = ?2)
+                AND (?3 IS NULL OR recorded_at >= ?3)
+                AND (?4 IS NULL OR recorded_at < ?4)
+            ORDER BY recorded_at, waypoint_symbol, trade_symbol
+            LIMIT ?5",
+        )?;
+        let rows = stmt.query_map(
+            params![query.waypoint, good, query.since, query.until, limit],
+            record,
+        )?;
+
+        rows.map(|row| row?.try_into()).collect()
+    }
+
+    /// The latest record of each good at each market in a system.
+    pub fn latest_prices(&self, system_symbol: &str) -> Result<Vec<PriceRecord>, anyhow::Error> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare_cached(
+            "SELECT waypoint_symbol, trade_symbol, recorded_at, good_type, supply,
+                activity, purchase_price, sell_price, trade_volume
+            FROM market_prices AS m
+            WHERE waypoint_symbol LIKE ?1 || '-%'
+                AND recorded_at = (
+                    SELECT MAX(recorded_at) FROM market_prices
+                    WHERE waypoint_symbol = m.waypoint_symbol AND trade_symbol = m.trade_symbol
+                )
+            ORDER BY waypoint_symbol, trade_symbol",
+        )?;
+        let rows = stmt.query_map(params![system_symbol], record)?;
+
+        rows.map(|row| row?.try_into()).collect()
+    }
+}
+
+/// The columns of a price record, before the enums are parsed.
+type RawRecord = (
+    String,
+    String,
+    i64,
+    String,
+    String,
+    Option<String>,
+    u64,
+    u64,
+    u64,
+);
+
+fn record(row: &rusqlite::Row) -> rusqlite::Result<RawRecord> {
+    Ok((
+        row.get(0)?,
+        row.get(1)?,
+        row.get(2)?,
+        row.get(3)?,
+        row.get(4)?,
+        row.get(5)?,
+        row.get(6)?,
+        row.get(7)?,
+        row.get(8)?,
+    ))
+}
+
+impl TryFrom<RawRecord> for PriceRecord {
+    type Error = anyhow::Error;
+
+    fn try_from(raw: RawRecord) -> Result<Self, Self::Error> {
+        let (
+            waypoint_symbol,
+            trade_symbol,
+            recorded_at,
+            good_type,
+            supply,
+            activity,
+            purchase_price,
+            sell_price,
+            trade_volume,
+        ) = raw;
+        Ok(PriceRecord {
+            waypoint_symbol,
+            trade_symbol: from_variant_name(trade_symbol)?,
+            recorded_at,
+            good_type: from_variant_name(good_type)?,
+            supply: from_variant_name(supply)?,
+            activity: activity.map(from_variant_name).transpose()?,
+            purchase_price,
+            sell_price,
+            trade_volume,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market(sell_price: u64) -> Market {
+        serde_json::from_value(serde_json::json!({
+            "symbol": "X1-A-1",
+            "exports": [],
+            "imports": [],
+            "exchange": [],
+            "tradeGoods": [
+                {
+                    "symbol": "IRON_ORE",
+                    "type": "IMPORT",
+                    "tradeVolume": 60,
+                    "supply": "SCARCE",
+                    "activity": "WEAK",
+                    "purchasePrice": 80,
+                    "sellPrice": sell_price
+                },
+                {
+                    "symbol": "FUEL",
+                    "type": "EXCHANGE",
+                    "tradeVolume": 100,
+                    "supply": "MODERATE",
+                    "purchasePrice": 72,
+                    "sellPrice": 68
+                }
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn queries_price_history_per_good() {
+        let store = Store::open_in_memory().unwrap();
+        store.record_prices(&market(40)).unwrap();
+        store.record_prices(&market(35)).unwrap();
+
+        let history = store
+            .price_history(&PriceQuery {
+                waypoint: Some("X1-A-1".to_string()),
+                good: Some(TradeSymbol::IronOre),
+                ..PriceQuery::default()
+            })
+            .unwrap();
+
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].sell_price, 40);
+        assert_eq!(history[1].sell_price, 35);
+        assert_eq!(history[1].supply, SupplyLevel::Scarce);
+        assert_eq!(history[1].activity, Some(ActivityLevel::Weak));
+
+        let all = store.price_history(&PriceQuery::default()).unwrap();
+        assert_eq!(all.len(), 4);
+        assert!(all.iter().any(|r| r.activity.is_none()));
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+
+use anyhow::Context;
+use chrono::DateTime;
+use rusqlite::params;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use super::{now_millis, Store};
+use crate::model::{Survey, TradeSymbol};
+
+/// A survey together with the expected value of a single extraction.
+#[derive(Serialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RankedSurvey {
+    pub survey: Survey,
+    /// The average price the deposits sell for in the survey's system.
+    /// Goods without a known price count as worthless.
+    pub value: u64,
+}
+
+impl Store {
+    pub fn put_survey(&self, survey: &Survey) -> Result<(), anyhow::Error> {
+        let expires_at = DateTime::parse_from_rfc3339(&survey.expiration)
+            .with_context(|| format!("Parsing expiration of survey `{}`", survey.signature))?
+            .timestamp_millis();
+
+        self.conn.lock().unwrap().execute(
+            "INSERT OR REPLACE INTO surveys (signature, waypoint_symbol, data, expires_at)
+            VALUES (?1, ?2, ?3, ?4)",
+            params![
+                survey.signature,
+                survey.symbol,
+                serde_json::to_string(survey)?,
+                expires_at
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Forget a survey, for example because the API reported it as exhausted.
+    pub fn remove_survey(&self, signature: &str) -> Result<(), anyhow::Error> {
+        self.conn.lock().unwrap().execute(
+            "DELETE FROM surveys WHERE signature = ?1",
+            params![signature],
+        )?;
+
+        Ok(())
+    }
+
+    /// All surveys that have not expired yet.
+    pub fn all_surveys(&self) -> Result<Vec<Survey>, anyhow::Error> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare_cached(
+            "SELECT data FROM surveys WHERE expires_at > ?1 ORDER BY waypoint_symbol, expires_at",
+        )?;
+        let rows = stmt.query_map(params![now_millis()], |row| row.get::<_, String>(0))?;
+
+        rows.map(|data| Ok(serde_json::from_str(&data?)?)).collect()
+    }
+
+    /// The surveys of a waypoint that have not expired yet.
+    /// Expired surveys are dropped along the way.
+    pub fn surveys_at(&self, waypoint_symbol: &str) -> Result<Vec<Survey>, anyhow::Error> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "DELETE FROM surveys WHERE expires_at <= ?1",
+            params![now_millis()],
+        )?;
+
+        let mut stmt = conn.prepare_cached(
+            "SELECT data FROM surveys WHERE waypoint_symbol = ?1 ORDER BY expires_at",
+        )?;
+        let rows = stmt.query_map(params![waypoint_symbol], |row| row.get::<_, String>(0))?;
+
+        rows.map(|data| Ok(serde_json::from_str(&data?)?)).collect()
+    }
+
+    /// The usable surveys of a waypoint, most valuable first.
+    pub fn ranked_surveys(
+        &self,
+        waypoint_symbol: &str,
+    ) -> Result<Vec<RankedSurvey>, anyhow::Error> {
+        let surveys = self.surveys_at(waypoint_symbol)?;
+        let system_symbol = waypoint_symbol
+            .rsplit_once('-')
+            .map_or(waypoint_symbol, |(system, _)| system);
+        let prices = self.best_sell_prices(system_symbol)?;
+
+        Ok(rank(surveys, &prices))
+    }
+
+    /// The best price each good currently sells for at any market in a system,
+    /// going by the latest record of each market.
+    pub fn best_sell_prices(
+        &self,
+        system_symbol: &str,
+    ) -> Result<HashMap<TradeSymbol, u64>, anyhow::Error> {
+        self.latest_prices_by(system_symbol, "MAX", "sell_price")
+    }
+}
+
+/// Sort surveys by the average value of their deposits.
+/// Larger deposits last for more extractions, so they win ties.
+fn rank(surveys: Vec<Survey>, prices: &HashMap<TradeSymbol, u64>) -> Vec<RankedSurvey> {
+    let mut ranked: Vec<_> = surveys
+        .into_iter()
+        .map(|survey| {
+            let total: u64 = survey
+                .deposits
+                .iter()
+                .map(|d| prices.get(&d.symbol).copied().unwrap_or_default())
+                .sum();
+            let value = total / survey.deposits.len().max(1) as u64;
+            RankedSurvey { survey, value }
+        })
+        .collect();
+
+    ranked.sort_by(|a, b| {
+        b.value
+            .cmp(&a.value)
+            .then_with(|| (b.survey.size as u8).cmp(&(a.survey.size as u8)))
+    });
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::DepositSize;
+
+    fn survey(signature: &str, deposits: &[&str], size: &str, expiration: &str) -> Survey {
+        serde_json::from_value(serde_json::json!({
+            "signature": signature,
+            "symbol": "X1-A-1",
+            "deposits": deposits.iter().map(|d| serde_json::json!({ "symbol": d })).collect::<Vec<_>>(),
+            "expiration": expiration,
+            "size": size
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn drops_expired_surveys() {
+        let store = Store::open_in_memory().unwrap();
+        store
+            .put_survey(&survey(
+                "old",
+                &["IRON_ORE"],
+                "SMALL",
+                "2020-01-01T00:00:00.000Z",
+            ))
+            .unwrap();
+        store
+            .put_survey(&survey(
+                "new",
+                &["IRON_ORE"],
+                "SMALL",
+                "2999-01-01T00:00:00.000Z",
+            ))
+            .unwrap();
+
+        let surveys = store.surveys_at("X1-A-1").unwrap();
+        assert_eq!(surveys.len(), 1);
+        assert_eq!(surveys[0].signature, "new");
+
+        store.remove_survey("new").unwrap();
+        assert!(store.surveys_at("X1-A-1").unwrap().is_empty());
+    }
+
+    #[test]
+    fn ranks_by_deposit_value_then_size() {
+        let expiration = "2999-01-01T00:00:00.000Z";
+        let prices = HashMap::from([(TradeSymbol::IronOre, 40), (TradeSymbol::GoldOre, 100)]);
+        let surveys = vec![
+            survey("iron", &["IRON_ORE", "IRON_ORE"], "LARGE", expiration),
+            survey(
+                "mixed-small",
+                &["IRON_ORE", "GOLD_ORE", "QUARTZ_SAND"],
+                "SMALL",
+                expiration,
+            ),
+            survey(
+                "mixed-large",
+                &["GOLD_ORE", "IRON_ORE", "QUARTZ_SAND"],
+                "LARGE",
+                expiration,
+            ),
+        ];
+
+        let ranked = rank(surveys, &prices);
+
+        assert_eq!(ranked[0].survey.signature, "mixed-large");
+        assert_eq!(ranked[0].value, 46);
+        assert_eq!(ranked[0].survey.size, DepositSize::Large);
+        assert_eq!(ranked[1].survey.signature, "mixed-small");
+        assert_eq!(ranked[2].survey.signature, "iron");
+    }
+}
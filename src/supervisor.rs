@@ -0,0 +1,168 @@
+use std::{future::Future, process::ExitCode, time::Duration};
+
+use tokio::sync::mpsc;
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
+use tracing::{event, instrument, Instrument, Level};
+
+/// How long tasks get to finish their current work after shutdown was requested.
+const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(30);
+
+/// The exit codes of the process.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Exit {
+    /// Shut down cleanly after receiving a signal.
+    Clean = 0,
+    /// A supervised task or command failed, which brought down the whole process.
+    TaskFailed = 1,
+    /// The configuration could not be loaded.
+    Config = 2,
+    /// Tasks did not finish within the grace period and were aborted.
+    ShutdownTimeout = 3,
+    /// The database could not be opened or migrated.
+    Store = 4,
+    /// No connection to the SpaceTraders API could be established.
+    Upstream = 5,
+    /// The command line arguments were not understood.
+    Usage = 6,
+}
+
+impl From<Exit> for ExitCode {
+    fn from(exit: Exit) -> Self {
+        ExitCode::from(exit as u8)
+    }
+}
+
+/// Runs the long-lived tasks of catfleet and coordinates their shutdown.
+///
+/// Every task receives the shutdown token through [`Supervisor::token`] and
+/// is expected to finish its current action and return once it is cancelled.
+/// If any task returns an error, all other tasks are shut down as well.
+#[derive(Debug)]
+pub struct Supervisor {
+    token: CancellationToken,
+    tracker: TaskTracker,
+    failures: mpsc::UnboundedSender<&'static str>,
+    failures_rx: mpsc::UnboundedReceiver<&'static str>,
+}
+
+impl Supervisor {
+    pub fn new() -> Self {
+        let (failures, failures_rx) = mpsc::unbounded_channel();
+
+        Self {
+            token: CancellationToken::new(),
+            tracker: TaskTracker::new(),
+            failures,
+            failures_rx,
+        }
+    }
+
+    /// A token that is cancelled once shutdown has been requested.
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// Spawn a supervised task.
+    pub fn spawn<F>(&self, name: &'static str, task: F)
+    where
+        F: Future<Output = Result<(), anyhow::Error>> + Send + 'static,
+    {
+        let failures = self.failures.clone();
+
+        self.tracker.spawn(
+            async move {
+                event!(Level::DEBUG, "Task started");
+                match task.await {
+                    Ok(()) => event!(Level::DEBUG, "Task finished"),
+                    Err(err) => {
+                        event!(Level::ERROR, "Task failed: {err:?}");
+                        let _ = failures.send(name);
+                    }
+                }
+            }
+            .instrument(tracing::info_span!("task", name)),
+        );
+    }
+
+    /// Wait until a shutdown signal is received or a task fails,
+    /// then shut down all tasks and report how that went.
+    #[instrument(name = "supervisor", level = Level::INFO, skip(self))]
+    pub async fn run(mut self) -> Exit {
+        let mut exit = tokio::select! {
+            signal = shutdown_signal() => {
+                event!(Level::INFO, "Received {signal}; shutting down");
+                Exit::Clean
+            }
+            Some(name) = self.failures_rx.recv() => {
+                event!(Level::ERROR, "Task `{name}` failed; shutting down");
+                Exit::TaskFailed
+            }
+        };
+
+        self.token.cancel();
+        self.tracker.close();
+
+        if tokio::time::timeout(SHUTDOWN_GRACE_PERIOD, self.tracker.wait())
+            .await
+            .is_err()
+        {
+            event!(
+                Level::WARN,
+                "Tasks did not finish within {SHUTDOWN_GRACE_PERIOD:?}; aborting"
+            );
+            exit = Exit::ShutdownTimeout;
+        }
+
+        // Tasks that failed while shutting down still count as a failure.
+        if exit == Exit::Clean && self.failures_rx.try_recv().is_ok() {
+            exit = Exit::TaskFailed;
+        }
+
+        event!(Level::INFO, ?exit, "Shutdown complete");
+        exit
+    }
+}
+
+/// Resolves once the process receives SIGINT or SIGTERM.
+async fn shutdown_signal() -> &'static str {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Installing the SIGINT handler should not fail");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Installing the SIGTERM handler should not fail")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => "SIGINT",
+        _ = terminate => "SIGTERM",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn failing_task_shuts_down_the_others() {
+        let supervisor = Supervisor::new();
+        let token = supervisor.token();
+
+        supervisor.spawn("well-behaved", async move {
+            token.cancelled().await;
+            Ok(())
+        });
+        supervisor.spawn("failing", async { Err(anyhow::anyhow!("boom")) });
+
+        assert_eq!(supervisor.run().await, Exit::TaskFailed);
+    }
+}